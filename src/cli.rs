@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+/// Options passed on the command line, e.g. `legend_of_mierda --seed 42`.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct CommandLineArgs {
+    /// Seed used for every run instead of a fresh random one.
+    pub seed: Option<u64>,
+    /// Write the inputs of each run to this file.
+    pub record: Option<PathBuf>,
+    /// Play back a recorded run instead of reading the keyboard.
+    pub replay: Option<PathBuf>,
+    /// Let a bot play one run without a window and print a summary.
+    pub headless: bool,
+    /// Game time after which a headless run is stopped.
+    pub time_limit: Option<Duration>,
+    /// Spawn this many chasing enemies in a headless run and print frame times.
+    pub crowd_bench: Option<u32>,
+}
+
+impl CommandLineArgs {
+    pub fn parse() -> Self {
+        Self::parse_from(std::env::args().skip(1))
+    }
+
+    pub fn parse_from(args: impl IntoIterator<Item = String>) -> Self {
+        let mut parsed = CommandLineArgs::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => match args.next().map(|value| value.parse::<u64>()) {
+                    Some(Ok(seed)) => parsed.seed = Some(seed),
+                    _ => eprintln!("--seed expects an unsigned integer"),
+                },
+                "--record" => match args.next() {
+                    Some(path) => parsed.record = Some(path.into()),
+                    None => eprintln!("--record expects a file path"),
+                },
+                "--replay" => match args.next() {
+                    Some(path) => parsed.replay = Some(path.into()),
+                    None => eprintln!("--replay expects a file path"),
+                },
+                "--headless" => parsed.headless = true,
+                "--time-limit" => match args.next().map(|value| value.parse::<f32>()) {
+                    Some(Ok(secs)) if secs.is_finite() && secs > 0.0 => {
+                        parsed.time_limit = Some(Duration::from_secs_f32(secs))
+                    }
+                    _ => eprintln!("--time-limit expects a positive number of seconds"),
+                },
+                "--crowd-bench" => match args.next().map(|value| value.parse::<u32>()) {
+                    Some(Ok(count)) if count > 0 => {
+                        parsed.crowd_bench = Some(count);
+                        parsed.headless = true;
+                    }
+                    _ => eprintln!("--crowd-bench expects a positive number of enemies"),
+                },
+                _ => eprintln!("ignoring unknown argument {}", arg),
+            }
+        }
+
+        parsed
+    }
+}
@@ -1,20 +1,33 @@
+use std::fmt;
+
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use pecs::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     entities::player::{Player, PlayerAttackEvent},
+    gameplay::experience::Perks,
     loading::CharacterSpritesheets,
+    replay::ReplayRecorder,
     sprites::*,
+    storage,
 };
 
-#[derive(Event, Copy, Clone, Reflect, Debug, PartialEq, Eq, Default)]
+const INPUT_MAP_FILE: &str = "input.ron";
+const GAMEPAD_AXIS_DEAD_ZONE: f32 = 0.5;
+
+#[derive(Event, Copy, Clone, Reflect, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct ControlEvent {
     pub up: bool,
     pub down: bool,
     pub left: bool,
     pub right: bool,
     pub attack: bool,
+    /// Switches the speargun to its next targeting mode.
+    #[serde(default)]
+    pub aim: bool,
 }
 
 pub fn control_character(
@@ -32,6 +45,7 @@ pub fn control_character(
         With<Player>,
     >,
     spritesheets: Res<CharacterSpritesheets>,
+    perks: Res<Perks>,
 ) {
     for control in ev_control.read() {
         for (entity, mut texture_atlas, mut velocity, mut char_animation, mut sprite, _player) in
@@ -66,7 +80,8 @@ pub fn control_character(
                 velocity.linvel.x = right - left;
                 velocity.linvel.y = up - down;
 
-                velocity.linvel = velocity.linvel.normalize_or_zero() * 100.;
+                velocity.linvel =
+                    velocity.linvel.normalize_or_zero() * 100. * perks.move_speed_multiplier();
 
                 let linvel_norm = velocity.linvel.distance(Vec2::ZERO);
 
@@ -101,13 +116,282 @@ pub fn control_character(
     }
 }
 
-pub fn keyboard_controls(input: Res<Input<KeyCode>>, mut ev_control: EventWriter<ControlEvent>) {
+// ---------
+// Input Map
+// ---------
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum ControlAction {
+    Up,
+    Down,
+    Left,
+    Right,
+    Aim,
+}
+
+impl ControlAction {
+    pub const ALL: [ControlAction; 5] = [
+        ControlAction::Up,
+        ControlAction::Down,
+        ControlAction::Left,
+        ControlAction::Right,
+        ControlAction::Aim,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ControlAction::Up => "UP",
+            ControlAction::Down => "DOWN",
+            ControlAction::Left => "LEFT",
+            ControlAction::Right => "RIGHT",
+            ControlAction::Aim => "AIM MODE",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum InputBinding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+    /// A stick pushed past the dead zone towards the positive or negative end of `axis`.
+    GamepadAxis {
+        axis: GamepadAxisType,
+        positive: bool,
+    },
+}
+
+impl InputBinding {
+    /// Both are keys, both gamepad buttons or both stick directions.
+    pub fn is_same_kind(&self, other: &InputBinding) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+impl fmt::Display for InputBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputBinding::Key(key_code) => write!(f, "{:?}", key_code),
+            InputBinding::GamepadButton(button_type) => write!(f, "PAD {:?}", button_type),
+            InputBinding::GamepadAxis { axis, positive } => {
+                write!(f, "PAD {:?}{}", axis, if *positive { "+" } else { "-" })
+            }
+        }
+    }
+}
+
+/// Maps keyboard keys and gamepad buttons/sticks to [`ControlAction`]s.
+/// Saved to `input.ron` in the data directory whenever it is rebound.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct InputMap {
+    pub up: Vec<InputBinding>,
+    pub down: Vec<InputBinding>,
+    pub left: Vec<InputBinding>,
+    pub right: Vec<InputBinding>,
+    #[serde(default = "default_aim_bindings")]
+    pub aim: Vec<InputBinding>,
+}
+
+fn default_aim_bindings() -> Vec<InputBinding> {
+    vec![
+        InputBinding::Key(KeyCode::Tab),
+        InputBinding::GamepadButton(GamepadButtonType::RightTrigger),
+    ]
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap {
+            up: vec![
+                InputBinding::Key(KeyCode::W),
+                InputBinding::GamepadButton(GamepadButtonType::DPadUp),
+                InputBinding::GamepadAxis {
+                    axis: GamepadAxisType::LeftStickY,
+                    positive: true,
+                },
+            ],
+            down: vec![
+                InputBinding::Key(KeyCode::S),
+                InputBinding::GamepadButton(GamepadButtonType::DPadDown),
+                InputBinding::GamepadAxis {
+                    axis: GamepadAxisType::LeftStickY,
+                    positive: false,
+                },
+            ],
+            left: vec![
+                InputBinding::Key(KeyCode::A),
+                InputBinding::GamepadButton(GamepadButtonType::DPadLeft),
+                InputBinding::GamepadAxis {
+                    axis: GamepadAxisType::LeftStickX,
+                    positive: false,
+                },
+            ],
+            right: vec![
+                InputBinding::Key(KeyCode::D),
+                InputBinding::GamepadButton(GamepadButtonType::DPadRight),
+                InputBinding::GamepadAxis {
+                    axis: GamepadAxisType::LeftStickX,
+                    positive: true,
+                },
+            ],
+            aim: default_aim_bindings(),
+        }
+    }
+}
+
+impl InputMap {
+    pub fn load() -> Self {
+        storage::load(INPUT_MAP_FILE).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        storage::save(INPUT_MAP_FILE, self);
+    }
+
+    pub fn bindings(&self, action: ControlAction) -> &Vec<InputBinding> {
+        match action {
+            ControlAction::Up => &self.up,
+            ControlAction::Down => &self.down,
+            ControlAction::Left => &self.left,
+            ControlAction::Right => &self.right,
+            ControlAction::Aim => &self.aim,
+        }
+    }
+
+    pub fn bindings_mut(&mut self, action: ControlAction) -> &mut Vec<InputBinding> {
+        match action {
+            ControlAction::Up => &mut self.up,
+            ControlAction::Down => &mut self.down,
+            ControlAction::Left => &mut self.left,
+            ControlAction::Right => &mut self.right,
+            ControlAction::Aim => &mut self.aim,
+        }
+    }
+
+    /// Binds `action` to `binding`, replacing the previous bindings of the same
+    /// kind so that keys, gamepad buttons and sticks are kept side by side.
+    pub fn rebind(&mut self, action: ControlAction, binding: InputBinding) {
+        let bindings = self.bindings_mut(action);
+        bindings.retain(|b| !b.is_same_kind(&binding));
+        bindings.push(binding);
+    }
+
+    pub fn describe(&self, action: ControlAction) -> String {
+        self.bindings(action)
+            .iter()
+            .map(|b| b.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(SystemParam)]
+pub struct InputDevices<'w> {
+    pub keys: Res<'w, Input<KeyCode>>,
+    pub gamepads: Res<'w, Gamepads>,
+    pub gamepad_buttons: Res<'w, Input<GamepadButton>>,
+    pub gamepad_axes: Res<'w, Axis<GamepadAxis>>,
+}
+
+impl<'w> InputDevices<'w> {
+    pub fn is_active(&self, binding: &InputBinding) -> bool {
+        match *binding {
+            InputBinding::Key(key_code) => self.keys.pressed(key_code),
+            InputBinding::GamepadButton(button_type) => self.gamepads.iter().any(|gamepad| {
+                self.gamepad_buttons
+                    .pressed(GamepadButton::new(gamepad, button_type))
+            }),
+            InputBinding::GamepadAxis { axis, positive } => self.gamepads.iter().any(|gamepad| {
+                let value = self
+                    .gamepad_axes
+                    .get(GamepadAxis::new(gamepad, axis))
+                    .unwrap_or(0.0);
+
+                match positive {
+                    true => value > GAMEPAD_AXIS_DEAD_ZONE,
+                    false => value < -GAMEPAD_AXIS_DEAD_ZONE,
+                }
+            }),
+        }
+    }
+
+    pub fn is_action_active(&self, input_map: &InputMap, action: ControlAction) -> bool {
+        input_map
+            .bindings(action)
+            .iter()
+            .any(|binding| self.is_active(binding))
+    }
+
+    /// Whether `action` was pressed this frame. Sticks never count, they have
+    /// no notion of a press.
+    pub fn is_action_just_activated(&self, input_map: &InputMap, action: ControlAction) -> bool {
+        input_map
+            .bindings(action)
+            .iter()
+            .any(|binding| match *binding {
+                InputBinding::Key(key_code) => self.keys.just_pressed(key_code),
+                InputBinding::GamepadButton(button_type) => self.gamepads.iter().any(|gamepad| {
+                    self.gamepad_buttons
+                        .just_pressed(GamepadButton::new(gamepad, button_type))
+                }),
+                InputBinding::GamepadAxis { .. } => false,
+            })
+    }
+
+    /// First key, gamepad button or stick direction pressed this frame, used for rebinding.
+    pub fn just_activated(&self) -> Option<InputBinding> {
+        if let Some(key_code) = self.keys.get_just_pressed().next() {
+            return Some(InputBinding::Key(*key_code));
+        }
+
+        if let Some(button) = self.gamepad_buttons.get_just_pressed().next() {
+            return Some(InputBinding::GamepadButton(button.button_type));
+        }
+
+        for gamepad in self.gamepads.iter() {
+            for axis in [
+                GamepadAxisType::LeftStickX,
+                GamepadAxisType::LeftStickY,
+                GamepadAxisType::RightStickX,
+                GamepadAxisType::RightStickY,
+            ] {
+                let value = self
+                    .gamepad_axes
+                    .get(GamepadAxis::new(gamepad, axis))
+                    .unwrap_or(0.0);
+
+                if value.abs() > GAMEPAD_AXIS_DEAD_ZONE {
+                    return Some(InputBinding::GamepadAxis {
+                        axis,
+                        positive: value > 0.0,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+}
+
+pub fn input_controls(
+    input_map: Res<InputMap>,
+    devices: InputDevices,
+    q_player: Query<(), With<Player>>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+    mut ev_control: EventWriter<ControlEvent>,
+) {
     let mut control = ControlEvent { ..default() };
 
-    control.right = input.pressed(KeyCode::D);
-    control.left = input.pressed(KeyCode::A);
-    control.up = input.pressed(KeyCode::W);
-    control.down = input.pressed(KeyCode::S);
+    control.right = devices.is_action_active(&input_map, ControlAction::Right);
+    control.left = devices.is_action_active(&input_map, ControlAction::Left);
+    control.up = devices.is_action_active(&input_map, ControlAction::Up);
+    control.down = devices.is_action_active(&input_map, ControlAction::Down);
+    control.aim = devices.is_action_just_activated(&input_map, ControlAction::Aim);
+
+    if let Some(mut recorder) = recorder {
+        if !q_player.is_empty() {
+            recorder.replay.frames.push(control);
+        }
+    }
 
     ev_control.send(control);
 }
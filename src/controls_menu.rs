@@ -0,0 +1,253 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+
+use crate::controls::{ControlAction, InputDevices, InputMap};
+use crate::loading::FontAssets;
+use crate::pause::PauseState;
+use crate::{ButtonColors, GameState};
+
+pub struct ControlsMenuPlugin;
+
+impl Plugin for ControlsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rebinding>()
+            .add_systems(
+                Update,
+                (
+                    open_controls_menu,
+                    click_controls_menu_button,
+                    capture_rebinding,
+                    update_binding_texts,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Menu).or_else(in_state(PauseState::Paused))),
+            )
+            .add_systems(OnExit(GameState::Menu), close_controls_menu)
+            .add_systems(OnExit(PauseState::Paused), close_controls_menu);
+    }
+}
+
+// ----------
+// Components
+// ----------
+
+#[derive(Component)]
+struct ControlsMenu;
+
+/// Put on a button to open the controls menu when it is pressed.
+#[derive(Component)]
+pub struct OpenControlsMenuButton;
+
+#[derive(Component, Clone, Copy)]
+enum ControlsMenuButton {
+    Rebind(ControlAction),
+    Reset,
+    Back,
+}
+
+#[derive(Component)]
+struct BindingText(ControlAction);
+
+// ---------
+// Resources
+// ---------
+
+/// Action waiting for a key or gamepad input to be bound to.
+#[derive(Resource, Default)]
+struct Rebinding(Option<ControlAction>);
+
+// -------
+// Systems
+// -------
+
+fn open_controls_menu(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    q_open_button: Query<&Interaction, (Changed<Interaction>, With<OpenControlsMenuButton>)>,
+    q_controls_menu: Query<(), With<ControlsMenu>>,
+) {
+    if !q_controls_menu.is_empty() {
+        return;
+    }
+
+    if !q_open_button.iter().any(|i| *i == Interaction::Pressed) {
+        return;
+    }
+
+    let text_style = |font_size: f32| TextStyle {
+        font: font_assets.pixeloid_mono.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            ControlsMenu,
+            Name::new("controls menu"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("CONTROLS", text_style(60.0)));
+
+            for action in ControlAction::ALL {
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            flex_direction: FlexDirection::Row,
+                            align_items: AlignItems::Center,
+                            column_gap: Val::Px(20.0),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        spawn_button(
+                            parent,
+                            action.name(),
+                            text_style(40.0),
+                            ControlsMenuButton::Rebind(action),
+                        );
+                        parent.spawn((
+                            TextBundle::from_section("", text_style(20.0)).with_style(Style {
+                                width: Val::Px(600.0),
+                                ..default()
+                            }),
+                            BindingText(action),
+                        ));
+                    });
+            }
+
+            spawn_button(parent, "RESET", text_style(40.0), ControlsMenuButton::Reset);
+            spawn_button(parent, "BACK", text_style(40.0), ControlsMenuButton::Back);
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    label: &str,
+    text_style: TextStyle,
+    button: ControlsMenuButton,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(200.0),
+                    height: Val::Px(50.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba_u8(0, 0, 0, 0).into(),
+                ..default()
+            },
+            ButtonColors {
+                normal: Color::WHITE,
+                hovered: Color::YELLOW,
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(label, text_style));
+        });
+}
+
+fn click_controls_menu_button(
+    mut commands: Commands,
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+    q_button: Query<(&Interaction, &ControlsMenuButton), Changed<Interaction>>,
+    q_controls_menu: Query<Entity, With<ControlsMenu>>,
+) {
+    for (interaction, button) in q_button.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match *button {
+            ControlsMenuButton::Rebind(action) => {
+                rebinding.0 = Some(action);
+            }
+            ControlsMenuButton::Reset => {
+                rebinding.0 = None;
+                *input_map = InputMap::default();
+                input_map.save();
+            }
+            ControlsMenuButton::Back => {
+                rebinding.0 = None;
+                for entity in q_controls_menu.iter() {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+        }
+    }
+}
+
+fn capture_rebinding(
+    mut rebinding: ResMut<Rebinding>,
+    mut input_map: ResMut<InputMap>,
+    devices: InputDevices,
+) {
+    let Some(action) = rebinding.0 else {
+        return;
+    };
+
+    if devices.keys.just_pressed(KeyCode::Escape) {
+        rebinding.0 = None;
+        return;
+    }
+
+    if let Some(binding) = devices.just_activated() {
+        input_map.rebind(action, binding);
+        input_map.save();
+        rebinding.0 = None;
+    }
+}
+
+fn update_binding_texts(
+    rebinding: Res<Rebinding>,
+    input_map: Res<InputMap>,
+    mut q_binding_text: Query<(&mut Text, &BindingText)>,
+) {
+    for (mut text, binding_text) in q_binding_text.iter_mut() {
+        let value = match rebinding.0 {
+            Some(action) if action == binding_text.0 => "PRESS A KEY...".to_string(),
+            _ => input_map.describe(binding_text.0),
+        };
+
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Run condition, true while the controls menu is shown.
+pub fn is_open(q_controls_menu: Query<(), With<ControlsMenu>>) -> bool {
+    !q_controls_menu.is_empty()
+}
+
+fn close_controls_menu(
+    mut commands: Commands,
+    mut rebinding: ResMut<Rebinding>,
+    q_controls_menu: Query<Entity, With<ControlsMenu>>,
+) {
+    rebinding.0 = None;
+    for entity in q_controls_menu.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
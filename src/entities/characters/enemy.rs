@@ -1,6 +1,8 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_kira_audio::prelude::*;
+use bevy_magic_light_2d::SpriteCamera;
 use bevy_rapier2d::prelude::Velocity;
 use bevy_rapier2d::prelude::*;
 use pecs::prelude::*;
@@ -9,13 +11,29 @@ use rand::Rng;
 use std::time::Duration;
 
 use crate::{
-    gameplay::scoring::Score, loading::load_texture_atlas, physics::ColliderBundle, sprites::*,
-    utils::CloneEntity, AudioAssets, GameState,
+    entities::items::xp_gem::SpawnXpGemEvent,
+    gameplay::{
+        experience::Perks,
+        scoring::{RunStats, Score},
+    },
+    ldtk::{CurrentLevel, LevelWalls},
+    loading::load_texture_atlas,
+    physics::ColliderBundle,
+    rng::{GameRng, GameRngSet},
+    sprites::*,
+    AudioAssets, GameState,
 };
 
 use crate::entities::player::Player;
 use crate::entities::text_indicator::SpawnTextIndicatorEvent;
 
+use super::enemy_archetype::{
+    hot_reload_enemy_archetype_table, insert_enemy_archetype_table, EnemyArchetype,
+    EnemyArchetypeId, EnemyArchetypeTable, EnemyArchetypeTableLoader,
+};
+use super::enemy_crowd::enemy_collision_groups;
+use super::enemy_spawn::{EnemySpawnPoint, SpawnArea, SpawnStrategy};
+
 // ----------
 // Components
 // ----------
@@ -29,18 +47,9 @@ pub struct DirectionUpdateTime {
 // Entities
 // --------
 
-#[derive(Clone, Copy, PartialEq, Debug, Default, Component, Reflect)]
-pub enum EnemyType {
-    #[default]
-    Mierda,
-    Pendejo,
-    Psychiatrist1,
-    Psychiatrist2,
-}
-
 #[derive(Clone, PartialEq, Debug, Default, Component, Reflect)]
 pub struct Enemy {
-    pub enemy_type: EnemyType,
+    pub archetype: EnemyArchetypeId,
     pub move_direction: Vec2,
     pub health: u16,
     pub hit_at: Option<Timer>,
@@ -56,6 +65,7 @@ pub struct EnemyBundle {
     pub enemy: Enemy,
     pub collider_bundle: ColliderBundle,
     pub active_events: ActiveEvents,
+    pub collision_groups: CollisionGroups,
     pub direction_update_time: DirectionUpdateTime,
     pub animated_character_sprite: AnimatedCharacterSprite,
 }
@@ -64,16 +74,51 @@ pub struct EnemyBundle {
 // LDTK
 // ----
 
+/// LDtk entities without a bundle of their own are enemies, their identifier
+/// is the id of their archetype. They only know that id, the ones that aren't
+/// dummies get the rest of their archetype in `fix_missing_ldtk_entities`.
+#[derive(Default, Bundle, Clone)]
+pub struct LdtkEnemyBundle {
+    pub enemy: Enemy,
+}
+
+impl LdtkEntity for LdtkEnemyBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _layer_instance: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        _: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> LdtkEnemyBundle {
+        let is_dummy = entity_instance
+            .get_bool_field("is_dummy")
+            .copied()
+            .unwrap_or(false);
+
+        LdtkEnemyBundle {
+            enemy: Enemy {
+                archetype: EnemyArchetypeId::new(entity_instance.identifier.clone()),
+                is_dummy,
+                ..default()
+            },
+        }
+    }
+}
+
+/// Builds an enemy from its `archetype`, found in the table under `id`.
 pub fn create_enemy_bundle(
     asset_server: &AssetServer,
     texture_atlasses: &mut Assets<TextureAtlas>,
     is_dummy: bool,
-    enemy_type: EnemyType,
+    id: &EnemyArchetypeId,
+    archetype: &EnemyArchetype,
+    rng: &mut impl Rng,
 ) -> EnemyBundle {
     let rotation_constraints = LockedAxes::ROTATION_LOCKED;
 
     let collider_bundle = ColliderBundle {
-        collider: Collider::cuboid(8., 26.),
+        collider: Collider::cuboid(archetype.collider.x, archetype.collider.y),
         rigid_body: RigidBody::Dynamic,
         friction: Friction {
             coefficient: 0.0,
@@ -83,62 +128,17 @@ pub fn create_enemy_bundle(
         ..Default::default()
     };
 
-    let (atlas_handle, spritesheet_type) = match enemy_type {
-        EnemyType::Mierda => (
-            load_texture_atlas(
-                MIERDA_ASSET_SHEET.to_string(),
-                asset_server,
-                5,
-                1,
-                None,
-                Vec2::ONE * 16.,
-                texture_atlasses,
-            ),
-            AnimatedCharacterType::NotAnimated,
-        ),
-        EnemyType::Pendejo => {
-            let (spritesheet_path, spritesheet_type) = PENDEJO_SPRITE_SHEETS
-                .choose(&mut rand::thread_rng())
-                .unwrap();
-
-            (
-                load_texture_atlas(
-                    spritesheet_path.to_string(),
-                    asset_server,
-                    SHEET_1_COLUMNS,
-                    SHEET_1_ROWS,
-                    None,
-                    Vec2::ONE * 64.,
-                    texture_atlasses,
-                ),
-                *spritesheet_type,
-            )
-        }
-        EnemyType::Psychiatrist1 => (
-            load_texture_atlas(
-                PSYCHIATRIST_1_ASSET_SHEET.to_string(),
-                asset_server,
-                1,
-                1,
-                None,
-                128. * Vec2::ONE,
-                texture_atlasses,
-            ),
-            AnimatedCharacterType::NotAnimated,
-        ),
-        EnemyType::Psychiatrist2 => (
-            load_texture_atlas(
-                PSYCHIATRIST_2_ASSET_SHEET.to_string(),
-                asset_server,
-                1,
-                1,
-                None,
-                128. * Vec2::ONE,
-                texture_atlasses,
-            ),
-            AnimatedCharacterType::NotAnimated,
-        ),
-    };
+    let sprite_sheet = &archetype.sprite_sheet;
+    let variant = sprite_sheet.variants.choose(rng).unwrap();
+    let atlas_handle = load_texture_atlas(
+        variant.path.clone(),
+        asset_server,
+        sprite_sheet.columns,
+        sprite_sheet.rows,
+        None,
+        sprite_sheet.tile_size,
+        texture_atlasses,
+    );
 
     let sprite_bundle = SpriteSheetBundle {
         texture_atlas: atlas_handle,
@@ -146,14 +146,11 @@ pub fn create_enemy_bundle(
         ..default()
     };
 
+    // no direction yet, the AI points it at the player on its first update
     let enemy = Enemy {
-        health: 100,
-        enemy_type,
-        move_direction: Vec2 {
-            x: rand::random::<f32>() * 2.0 - 1.0,
-            y: rand::random::<f32>() * 2.0 - 1.0,
-        }
-        .normalize(),
+        health: archetype.health,
+        archetype: id.clone(),
+        move_direction: Vec2::ZERO,
         hit_at: None,
         is_dummy,
         marked_for_despawn: false,
@@ -169,16 +166,43 @@ pub fn create_enemy_bundle(
         spritesheet_bundle: sprite_bundle,
         collider_bundle,
         active_events: ActiveEvents::COLLISION_EVENTS,
+        collision_groups: enemy_collision_groups(),
         enemy,
         direction_update_time: DirectionUpdateTime {
-            timer: Timer::new(Duration::from_secs(5), TimerMode::Once),
+            timer: Timer::new(Duration::from_millis(250), TimerMode::Repeating),
         },
         animated_character_sprite: AnimatedCharacterSprite {
-            animated_character_type: spritesheet_type,
+            animated_character_type: variant.animation,
         },
     }
 }
 
+/// Resources needed to build enemies from their archetypes.
+#[derive(SystemParam)]
+pub struct EnemySpawner<'w> {
+    pub asset_server: Res<'w, AssetServer>,
+    pub texture_atlasses: ResMut<'w, Assets<TextureAtlas>>,
+    pub archetypes: Res<'w, EnemyArchetypeTable>,
+    pub rng: ResMut<'w, GameRng>,
+}
+
+impl<'w> EnemySpawner<'w> {
+    /// A new, non-dummy enemy of archetype `id` with its archetype's health,
+    /// `None` if there is no such archetype.
+    pub fn bundle(&mut self, id: &EnemyArchetypeId) -> Option<EnemyBundle> {
+        let archetype = self.archetypes.get(id)?;
+
+        Some(create_enemy_bundle(
+            &self.asset_server,
+            &mut self.texture_atlasses,
+            false,
+            id,
+            archetype,
+            &mut *self.rng,
+        ))
+    }
+}
+
 // ------
 // Events
 // ------
@@ -192,109 +216,106 @@ pub struct EnemyHitEvent {
 #[derive(Event, Clone)]
 pub struct SpawnEnemyEvent {
     pub count: u32,
-    pub enemy_type: EnemyType,
+    pub archetype: EnemyArchetypeId,
+    pub strategy: SpawnStrategy,
 }
 
 // --------------
 // Event Handlers
 // --------------
 
+/// Spawns `enemy_bundle` at `transform` as a child of `level`.
+pub fn spawn_enemy(
+    commands: &mut Commands,
+    level: Entity,
+    mut enemy_bundle: EnemyBundle,
+    transform: Transform,
+) -> Entity {
+    enemy_bundle.spritesheet_bundle.transform = transform;
+    let name = Name::new(enemy_bundle.enemy.archetype.to_string());
+
+    commands.spawn((enemy_bundle, name)).set_parent(level).id()
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_spawn_enemy(
     mut commands: Commands,
     mut ev_spawn_enemy: EventReader<SpawnEnemyEvent>,
-    level_selection: Res<LevelSelection>,
-    levels: Query<(Entity, &LevelIid)>,
-    projects: Query<&Handle<LdtkProject>>,
-    project_assets: Res<Assets<LdtkProject>>,
-    enemies: Query<(Entity, &Parent, &Enemy)>,
+    current_level: CurrentLevel,
     q_player_query: Query<(Entity, &Transform, &Player)>,
+    q_camera: Query<(&Transform, &OrthographicProjection), With<SpriteCamera>>,
+    q_spawn_points: Query<&GlobalTransform, With<EnemySpawnPoint>>,
+    level_walls: Res<LevelWalls>,
+    mut spawner: EnemySpawner,
 ) {
     if q_player_query.iter().count() == 0 {
         return;
     }
 
-    let mut rng = rand::thread_rng();
     let player_translation = q_player_query.single().1.translation;
 
     for ev_spawn in ev_spawn_enemy.read() {
-        for (_, level_iid) in levels.iter() {
-            let project = project_assets.get(projects.single()).unwrap();
-            let level = project.get_raw_level_by_iid(level_iid.get()).unwrap();
-            let max_level_dimension = level.px_wid.max(level.px_hei) as f32;
-
-            if level_selection.is_match(
-                &LevelIndices {
-                    level: 0,
-                    ..default()
-                },
-                level,
-            ) {
-                for _i in 0..ev_spawn.count {
-                    for (mierda_entity, enemy_parent, mierda) in enemies.iter() {
-                        if !mierda.is_dummy {
-                            continue;
-                        }
-                        if mierda.enemy_type != ev_spawn.enemy_type {
-                            continue;
-                        }
-
-                        let enemy_parent = enemy_parent.get();
-                        let mut parent = commands.entity(enemy_parent);
-
-                        let mut new_entity: Option<Entity> = None;
-                        parent.with_children(|cm| {
-                            let ne = cm.spawn_empty().id();
-                            new_entity = Some(ne);
-                        });
-
-                        // generate random position
-
-                        let mut offset_position = Vec3::new(0.0, 0.0, 0.);
-                        let mut enemy_position = player_translation + offset_position;
-
-                        while (player_translation - enemy_position).length()
-                            < max_level_dimension / 2.0
-                            || enemy_position.x < 0.0 + 24.0
-                            || enemy_position.x > (level.px_wid as f32) - 24.0
-                            || enemy_position.y < 0.0 + 24.0
-                            || enemy_position.y > (level.px_hei as f32) - 24.0
-                        {
-                            let r = rng.gen_range(0.0..1000.0);
-                            let angle = rng.gen_range(0.0..std::f32::consts::PI * 2.0);
-
-                            offset_position =
-                                Vec3::new(r * f32::sin(angle), r * f32::cos(angle), 0.);
-                            enemy_position = player_translation + offset_position;
-                        }
-
-                        let transform =
-                            Transform::from_translation(enemy_position).with_scale(Vec3::ONE * 0.5);
-
-                        let new_entity = new_entity.unwrap();
-                        commands.entity(new_entity).insert(Enemy {
-                            enemy_type: ev_spawn.enemy_type,
-                            is_dummy: false,
-                            health: match ev_spawn.enemy_type {
-                                EnemyType::Mierda => 50,
-                                EnemyType::Pendejo => 100,
-                                EnemyType::Psychiatrist1 => 5000,
-                                EnemyType::Psychiatrist2 => 5000,
-                            },
-                            move_direction: Vec2::ZERO,
-                            hit_at: None,
-                            marked_for_despawn: false,
-                        });
-
-                        commands.add(CloneEntity {
-                            source: mierda_entity,
-                            destination: new_entity,
-                        });
-
-                        commands.entity(new_entity).insert(transform);
-                        break;
-                    }
-                }
-            }
+        let Some(archetype) = spawner.archetypes.get(&ev_spawn.archetype) else {
+            warn!("no enemy archetype {}", ev_spawn.archetype);
+            continue;
+        };
+        let scale = archetype.scale;
+
+        let Some((level_entity, level_transform, level)) = current_level.get() else {
+            warn!(
+                "no level to spawn {} {} in",
+                ev_spawn.count, ev_spawn.archetype
+            );
+            continue;
+        };
+
+        // strategies work in level-local coordinates
+        let level_origin = level_transform.translation().truncate();
+        let size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+
+        let view = q_camera
+            .get_single()
+            .ok()
+            .map(|(camera_transform, projection)| {
+                let camera_origin = camera_transform.translation.truncate() - level_origin;
+                Rect::from_corners(
+                    camera_origin + projection.area.min,
+                    camera_origin + projection.area.max,
+                )
+            });
+
+        let spawn_points: Vec<Vec2> = q_spawn_points
+            .iter()
+            .map(|point| point.translation().truncate() - level_origin)
+            .filter(|point| Rect::from_corners(Vec2::ZERO, size).contains(*point))
+            .collect();
+
+        let spawn_area = SpawnArea {
+            size,
+            walls: level_walls.levels.get(&level_entity),
+            player: player_translation.truncate(),
+            view,
+            spawn_points: &spawn_points,
+        };
+
+        let positions = spawn_area.positions(ev_spawn.strategy, ev_spawn.count, &mut *spawner.rng);
+        if positions.len() < ev_spawn.count as usize {
+            warn!(
+                "no room for {} of {} {}",
+                ev_spawn.count as usize - positions.len(),
+                ev_spawn.count,
+                ev_spawn.archetype
+            );
+        }
+
+        for position in positions {
+            let transform = Transform::from_translation(position.extend(player_translation.z))
+                .with_scale(Vec3::ONE * scale);
+
+            let Some(enemy_bundle) = spawner.bundle(&ev_spawn.archetype) else {
+                continue;
+            };
+            spawn_enemy(&mut commands, level_entity, enemy_bundle, transform);
         }
     }
 }
@@ -305,7 +326,8 @@ pub fn handle_enemy_hit(
     mut enemies: Query<(Entity, &Transform, &mut Velocity, &mut Enemy)>,
     mut ev_enemy_hit: EventReader<EnemyHitEvent>,
     mut ev_spawn_text_indicator: EventWriter<SpawnTextIndicatorEvent>,
-
+    perks: Res<Perks>,
+    archetypes: Res<EnemyArchetypeTable>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
 ) {
@@ -326,12 +348,11 @@ pub fn handle_enemy_hit(
             enemy_velocity.linvel.x += vector_attack.x * 500.;
             enemy_velocity.linvel.y += vector_attack.y * 500.;
 
-            let damage = match enemy.enemy_type {
-                EnemyType::Mierda => (1.0 * event.damage as f32) as u16,
-                EnemyType::Pendejo => (0.5 * event.damage as f32) as u16,
-                EnemyType::Psychiatrist1 => (1.0 * event.damage as f32) as u16,
-                EnemyType::Psychiatrist2 => (1.0 * event.damage as f32) as u16,
-            };
+            let event_damage = event.damage as f32 * perks.damage_multiplier();
+            let damage_taken = archetypes
+                .get(&enemy.archetype)
+                .map_or(1.0, |archetype| archetype.damage_taken);
+            let damage = (damage_taken * event_damage) as u16;
 
             let timer = Timer::new(std::time::Duration::from_millis(200), TimerMode::Once);
             enemy.hit_at = Some(timer.clone());
@@ -356,10 +377,13 @@ pub fn handle_enemy_hit(
 
 pub fn despawn_dead_enemies(
     mut commands: Commands,
-    mut enemies: Query<(Entity, &Transform, &mut Velocity, &mut Enemy)>,
+    mut enemies: Query<(Entity, &Parent, &Transform, &mut Enemy)>,
     mut score: ResMut<Score>,
+    mut run_stats: ResMut<RunStats>,
+    archetypes: Res<EnemyArchetypeTable>,
+    mut ev_spawn_xp_gem: EventWriter<SpawnXpGemEvent>,
 ) {
-    for (e, _, _, mut enemy) in enemies.iter_mut() {
+    for (e, parent, transform, mut enemy) in enemies.iter_mut() {
         if enemy.health != 0 {
             continue;
         }
@@ -369,12 +393,21 @@ pub fn despawn_dead_enemies(
         }
 
         enemy.marked_for_despawn = true;
-        score.score += match enemy.enemy_type {
-            EnemyType::Mierda => 100,
-            EnemyType::Psychiatrist1 => 5000,
-            EnemyType::Psychiatrist2 => 5000,
-            EnemyType::Pendejo => 50,
-        };
+        *run_stats
+            .enemies_killed
+            .entry(enemy.archetype.clone())
+            .or_default() += 1;
+
+        // removed from the archetypes by a hot reload, no score or drop then
+        if let Some(archetype) = archetypes.get(&enemy.archetype) {
+            score.score += archetype.score;
+
+            ev_spawn_xp_gem.send(SpawnXpGemEvent {
+                level: parent.get(),
+                transform: Transform::from_translation(transform.translation),
+                xp: archetype.xp,
+            });
+        }
 
         commands
             .promise(|| (e))
@@ -398,17 +431,21 @@ pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app
+        app.init_asset::<EnemyArchetypeTable>()
+            .init_asset_loader::<EnemyArchetypeTableLoader>()
+            .register_default_ldtk_entity::<LdtkEnemyBundle>()
             // Event Handlers
             .add_event::<EnemyHitEvent>()
             .add_event::<SpawnEnemyEvent>()
+            .add_systems(OnExit(GameState::Loading), insert_enemy_archetype_table)
             // Event Handlers
             .add_systems(
                 Update,
                 (
+                    hot_reload_enemy_archetype_table,
                     // Events
                     handle_enemy_hit,
-                    handle_spawn_enemy,
+                    handle_spawn_enemy.in_set(GameRngSet::SpawnEnemies),
                     // Rest
                     despawn_dead_enemies,
                 )
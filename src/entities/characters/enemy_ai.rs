@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+
+use crate::{
+    entities::player::Player,
+    sprites::{AnimationDirection, CharacterAnimation},
+    GameState,
+};
+
+use super::enemy::{DirectionUpdateTime, Enemy};
+use super::enemy_pathfinding::PlayerFlowField;
+
+// -------
+// Systems
+// -------
+
+/// Points enemies along the player flow field, around walls, once their
+/// direction timer runs out; animated ones turn to face where they go.
+pub fn update_enemies_move_direction(
+    time: Res<Time>,
+    flow_field: Res<PlayerFlowField>,
+    q_player: Query<&Transform, With<Player>>,
+    mut q_enemies: Query<
+        (
+            &Transform,
+            &mut DirectionUpdateTime,
+            &mut CharacterAnimation,
+            &mut Enemy,
+        ),
+        Without<Player>,
+    >,
+) {
+    let Ok(player_transform) = q_player.get_single() else {
+        return;
+    };
+
+    let player_position = player_transform.translation.truncate();
+
+    for (transform, mut direction_update_timer, mut animation, mut enemy) in
+        q_enemies.iter_mut().filter(|(_, _, _, e)| !e.is_dummy)
+    {
+        direction_update_timer.timer.tick(time.delta());
+
+        if !direction_update_timer.timer.finished() && enemy.move_direction != Vec2::ZERO {
+            continue;
+        }
+
+        let position = transform.translation.truncate();
+        enemy.move_direction = flow_field
+            .direction_at(position)
+            .unwrap_or_else(|| (player_position - position).normalize_or_zero());
+
+        let angle =
+            enemy.move_direction.x.atan2(enemy.move_direction.y) - std::f32::consts::FRAC_PI_4;
+
+        let mut normalized_angle = angle / std::f32::consts::FRAC_PI_2;
+        if normalized_angle < 0.0 {
+            normalized_angle += 4.0;
+        }
+
+        animation.direction = match normalized_angle.ceil() as usize {
+            4 => AnimationDirection::Up,
+            1 => AnimationDirection::Right,
+            2 => AnimationDirection::Down,
+            3 => AnimationDirection::Left,
+            _ => AnimationDirection::Left,
+        };
+    }
+}
+
+// ------
+// Plugin
+// ------
+
+pub struct EnemyAiPlugin;
+
+impl Plugin for EnemyAiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_enemies_move_direction.run_if(in_state(GameState::GamePlay)),
+        );
+    }
+}
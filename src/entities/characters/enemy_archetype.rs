@@ -0,0 +1,224 @@
+use std::fmt;
+use std::time::Duration;
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::{BoxedFuture, HashMap};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::gameplay::level_waves::deserialize_secs;
+use crate::loading::EnemyAssets;
+use crate::sprites::AnimatedCharacterType;
+
+// -----
+// Asset
+// -----
+
+/// Name of an archetype in the archetype table, e.g. `"Mierda"`. Wave scripts,
+/// attacks and saved runs refer to enemies by it, and LDtk enemies use their
+/// entity identifier.
+#[derive(
+    Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Default, Reflect, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct EnemyArchetypeId(pub String);
+
+impl EnemyArchetypeId {
+    pub fn new(name: impl Into<String>) -> Self {
+        EnemyArchetypeId(name.into())
+    }
+}
+
+impl fmt::Display for EnemyArchetypeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// One sprite sheet an enemy can be drawn with.
+#[derive(Deserialize, Clone, Debug)]
+pub struct SpriteSheetVariant {
+    /// Path of the sheet, relative to `assets`.
+    pub path: String,
+    /// Walk and stand animations of the sheet, `NotAnimated` for single frames.
+    #[serde(default = "not_animated")]
+    pub animation: AnimatedCharacterType,
+}
+
+fn not_animated() -> AnimatedCharacterType {
+    AnimatedCharacterType::NotAnimated
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SpriteSheetLayout {
+    /// One of them is picked at random for each enemy.
+    pub variants: Vec<SpriteSheetVariant>,
+    pub columns: usize,
+    pub rows: usize,
+    /// Size of a frame in pixels.
+    pub tile_size: Vec2,
+}
+
+/// How an enemy moves.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum EnemyBehaviour {
+    /// Walks towards the player at `speed`, swaying up to `sway` degrees off course.
+    Chase {
+        speed: f32,
+        #[serde(default)]
+        sway: f32,
+    },
+    /// Stays where it was spawned.
+    Stationary,
+}
+
+/// Stats, looks and behaviour of an enemy type.
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnemyArchetype {
+    pub health: u16,
+    /// Multiplies the damage of the player's weapons.
+    #[serde(default = "default_damage_taken")]
+    pub damage_taken: f32,
+    /// Damage dealt to the player on touch, before armor.
+    pub contact_damage: u16,
+    /// Added to the score when killed.
+    pub score: u32,
+    /// XP of the gem dropped when killed.
+    pub xp: u32,
+    pub sprite_sheet: SpriteSheetLayout,
+    /// Scale of the sprite and collider.
+    pub scale: f32,
+    /// Half width and half height of the collider, before scaling.
+    pub collider: Vec2,
+    pub behaviour: EnemyBehaviour,
+}
+
+fn default_damage_taken() -> f32 {
+    1.0
+}
+
+/// Every enemy archetype by id, loaded from `assets/enemies/enemy.archetypes.ron`.
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, Debug)]
+pub struct EnemyArchetypeTable {
+    /// How often enemies that stay in touch with the player hurt them again.
+    #[serde(deserialize_with = "deserialize_secs")]
+    pub contact_interval: Duration,
+    pub archetypes: HashMap<EnemyArchetypeId, EnemyArchetype>,
+}
+
+impl EnemyArchetypeTable {
+    pub fn get(&self, id: &EnemyArchetypeId) -> Option<&EnemyArchetype> {
+        self.archetypes.get(id)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.archetypes.is_empty() {
+            return Err("no archetypes".to_string());
+        }
+        if self.contact_interval.is_zero() {
+            return Err("zero contact_interval".to_string());
+        }
+
+        for (id, archetype) in self.archetypes.iter() {
+            if archetype.health == 0 {
+                return Err(format!("{} has no health", id));
+            }
+            if archetype.sprite_sheet.variants.is_empty() {
+                return Err(format!("{} has no sprite sheet", id));
+            }
+            if archetype.sprite_sheet.columns == 0 || archetype.sprite_sheet.rows == 0 {
+                return Err(format!("{} sprite sheet has no frames", id));
+            }
+            if archetype.scale <= 0.0 || archetype.collider.min_element() <= 0.0 {
+                return Err(format!("{} needs a positive scale and collider size", id));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ------
+// Loader
+// ------
+
+#[derive(Debug, Error)]
+pub enum EnemyArchetypeTableLoaderError {
+    #[error("could not read enemy archetypes: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse enemy archetypes: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid enemy archetypes: {0}")]
+    Invalid(String),
+}
+
+#[derive(Default)]
+pub struct EnemyArchetypeTableLoader;
+
+impl AssetLoader for EnemyArchetypeTableLoader {
+    type Asset = EnemyArchetypeTable;
+    type Settings = ();
+    type Error = EnemyArchetypeTableLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let archetype_table = ron::de::from_bytes::<EnemyArchetypeTable>(&bytes)?;
+            archetype_table
+                .validate()
+                .map_err(EnemyArchetypeTableLoaderError::Invalid)?;
+
+            Ok(archetype_table)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["archetypes.ron"]
+    }
+}
+
+// -------
+// Systems
+// -------
+
+pub fn insert_enemy_archetype_table(
+    mut commands: Commands,
+    enemy_assets: Res<EnemyAssets>,
+    archetype_tables: Res<Assets<EnemyArchetypeTable>>,
+) {
+    let archetype_table = archetype_tables
+        .get(&enemy_assets.archetypes)
+        .expect("enemy archetypes are loaded with the other assets");
+
+    commands.insert_resource(archetype_table.clone());
+}
+
+/// Picks up edits of the enemy archetypes while the game runs. Health and
+/// looks apply to enemies spawned afterwards, everything else right away.
+/// Enemies whose archetype was removed keep their health and stop moving.
+pub fn hot_reload_enemy_archetype_table(
+    mut er_archetype_table: EventReader<AssetEvent<EnemyArchetypeTable>>,
+    archetype_tables: Res<Assets<EnemyArchetypeTable>>,
+    mut archetype_table: ResMut<EnemyArchetypeTable>,
+) {
+    for event in er_archetype_table.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+
+        let Some(reloaded) = archetype_tables.get(*id) else {
+            continue;
+        };
+
+        *archetype_table = reloaded.clone();
+        info!("enemy archetypes reloaded");
+    }
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_rapier2d::prelude::*;
+
+use crate::GameState;
+
+use super::enemy::Enemy;
+use super::enemy_ai::update_enemies_move_direction;
+use super::enemy_archetype::{EnemyArchetypeTable, EnemyBehaviour};
+
+/// Enemies closer than this push each other apart. Also the size of the
+/// buckets neighbours are looked up in.
+const SEPARATION_RADIUS: f32 = 16.0;
+/// Speed at which crowded enemies move apart.
+const SEPARATION_SPEED: f32 = 40.0;
+
+/// Enemies don't collide with each other, separation keeps them apart.
+pub fn enemy_collision_groups() -> CollisionGroups {
+    CollisionGroups::new(Group::GROUP_2, Group::ALL - Group::GROUP_2)
+}
+
+// -------
+// Systems
+// -------
+
+/// Moves enemies along their direction, as fast as their archetype says, and
+/// away from the enemies crowding them. Enemies that were just hit are left to
+/// be knocked back.
+pub fn steer_enemies(
+    time: Res<Time>,
+    archetypes: Res<EnemyArchetypeTable>,
+    mut q_enemies: Query<(Entity, &Transform, &mut Velocity, &mut Enemy)>,
+) {
+    let enemies: Vec<(Entity, Vec2)> = q_enemies
+        .iter()
+        .filter(|(_, _, _, enemy)| !enemy.is_dummy)
+        .map(|(entity, transform, _, _)| (entity, transform.translation.truncate()))
+        .collect();
+
+    let bucket = |position: Vec2| (position / SEPARATION_RADIUS).floor().as_ivec2();
+
+    let mut buckets: HashMap<IVec2, Vec<usize>> = HashMap::new();
+    for (i, (_, position)) in enemies.iter().enumerate() {
+        buckets.entry(bucket(*position)).or_default().push(i);
+    }
+
+    let sway_angle = time.elapsed_seconds().cos();
+
+    for (i, (entity, position)) in enemies.iter().enumerate() {
+        let Ok((_, _, mut v, mut enemy)) = q_enemies.get_mut(*entity) else {
+            continue;
+        };
+
+        if let Some(timer) = enemy.hit_at.as_mut() {
+            timer.tick(time.delta());
+            if !timer.finished() {
+                continue;
+            }
+            enemy.hit_at = None;
+        }
+
+        let behaviour = archetypes
+            .get(&enemy.archetype)
+            .map(|archetype| archetype.behaviour);
+
+        let Some(EnemyBehaviour::Chase { speed, sway }) = behaviour else {
+            v.linvel = Vec2::ZERO;
+            continue;
+        };
+
+        let cell = bucket(*position);
+        let neighbours = (-1..=1)
+            .flat_map(|x| (-1..=1).map(move |y| cell + IVec2::new(x, y)))
+            .filter_map(|neighbour_cell| buckets.get(&neighbour_cell))
+            .flatten()
+            .filter(|j| **j != i);
+
+        let mut separation = Vec2::ZERO;
+        for j in neighbours {
+            let away = *position - enemies[*j].1;
+            let distance = away.length();
+            if distance >= SEPARATION_RADIUS {
+                continue;
+            }
+
+            // enemies on top of each other split along a direction of their own
+            let away = match distance > f32::EPSILON {
+                true => away / distance,
+                false => Vec2::from_angle(i as f32),
+            };
+            separation += away * (1.0 - distance / SEPARATION_RADIUS);
+        }
+
+        let direction =
+            Vec2::from_angle(sway_angle * sway.to_radians()).rotate(enemy.move_direction);
+        v.linvel = direction * speed + separation.clamp_length_max(1.0) * SEPARATION_SPEED;
+    }
+}
+
+// ------
+// Plugin
+// ------
+
+pub struct EnemyCrowdPlugin;
+
+impl Plugin for EnemyCrowdPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            steer_enemies
+                .after(update_enemies_move_direction)
+                .run_if(in_state(GameState::GamePlay)),
+        );
+    }
+}
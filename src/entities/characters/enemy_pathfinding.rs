@@ -0,0 +1,231 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+use crate::{
+    entities::player::Player,
+    ldtk::{LevelWalls, WallGrid},
+    GameState,
+};
+
+use super::enemy_ai::update_enemies_move_direction;
+
+const UNREACHABLE: u32 = u32::MAX;
+
+/// Cells a search expands per frame at most. A full search of the 32x32 first
+/// level takes about 0.1 ms in a release build, a 128x128 level about 1.7 ms,
+/// so bigger levels are searched over a few frames instead of in one.
+const SEARCH_CELLS_PER_FRAME: usize = 2048;
+
+const NEIGHBOURS: [(i32, i32); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+
+// ---------
+// Resources
+// ---------
+
+/// Steps to the player from every free cell of the player's level, shared by
+/// all chasing enemies. When the player enters another cell a new search
+/// starts, at most `SEARCH_CELLS_PER_FRAME` cells a frame, and enemies follow
+/// the previous field until it is done.
+#[derive(Resource, Default)]
+pub struct PlayerFlowField {
+    level: Option<Entity>,
+    target: Option<GridCoords>,
+    grid: WallGrid,
+    distances: Vec<u32>,
+    search: Option<FlowFieldSearch>,
+}
+
+/// Breadth first search over the free cells, starting at the player's cell.
+struct FlowFieldSearch {
+    target: GridCoords,
+    distances: Vec<u32>,
+    queue: VecDeque<GridCoords>,
+}
+
+impl PlayerFlowField {
+    fn distance(&self, coords: GridCoords) -> u32 {
+        if !self.grid.contains(coords) {
+            return UNREACHABLE;
+        }
+
+        self.distances
+            .get((coords.y * self.grid.width + coords.x) as usize)
+            .copied()
+            .unwrap_or(UNREACHABLE)
+    }
+
+    fn start_search(&mut self, target: GridCoords) {
+        let grid = &self.grid;
+
+        let mut distances = vec![UNREACHABLE; (grid.width * grid.height) as usize];
+        let mut queue = VecDeque::new();
+
+        // the player may overlap a wall cell, start from it anyway
+        if grid.contains(target) {
+            distances[(target.y * grid.width + target.x) as usize] = 0;
+            queue.push_back(target);
+        }
+
+        self.search = Some(FlowFieldSearch {
+            target,
+            distances,
+            queue,
+        });
+    }
+
+    /// Expands up to `budget` cells of the running search, its distances
+    /// replace the current ones once it is done.
+    fn continue_search(&mut self, budget: usize) {
+        let grid = &self.grid;
+        let index = |coords: GridCoords| (coords.y * grid.width + coords.x) as usize;
+
+        let Some(search) = self.search.as_mut() else {
+            return;
+        };
+
+        for _ in 0..budget {
+            let Some(cell) = search.queue.pop_front() else {
+                break;
+            };
+            let distance = search.distances[index(cell)] + 1;
+
+            for (x, y) in &NEIGHBOURS[..4] {
+                let neighbour = GridCoords {
+                    x: cell.x + x,
+                    y: cell.y + y,
+                };
+
+                if grid.is_wall(neighbour) || search.distances[index(neighbour)] != UNREACHABLE {
+                    continue;
+                }
+
+                search.distances[index(neighbour)] = distance;
+                search.queue.push_back(neighbour);
+            }
+        }
+
+        if !search.queue.is_empty() {
+            return;
+        }
+
+        if let Some(search) = self.search.take() {
+            self.distances = search.distances;
+            self.target = Some(search.target);
+        }
+    }
+
+    /// Direction from a level-local `position` towards the next cell on the way
+    /// to the player. `None` in the player's cell, in walls, or where the player
+    /// can't be reached; enemies then head straight for the player.
+    pub fn direction_at(&self, position: Vec2) -> Option<Vec2> {
+        let cell = self.grid.cell_at(position);
+        let distance = self.distance(cell);
+        if distance == 0 || distance == UNREACHABLE {
+            return None;
+        }
+
+        let (next, next_distance) = NEIGHBOURS
+            .iter()
+            // diagonal steps can't cut wall corners
+            .filter(|(x, y)| {
+                !self.grid.is_wall(GridCoords {
+                    x: cell.x + x,
+                    y: cell.y,
+                }) && !self.grid.is_wall(GridCoords {
+                    x: cell.x,
+                    y: cell.y + y,
+                })
+            })
+            .map(|(x, y)| {
+                let neighbour = GridCoords {
+                    x: cell.x + x,
+                    y: cell.y + y,
+                };
+                (neighbour, self.distance(neighbour))
+            })
+            .min_by_key(|(_, distance)| *distance)?;
+
+        if next_distance >= distance {
+            return None;
+        }
+
+        Some((self.grid.cell_center(next) - position).normalize_or_zero())
+    }
+}
+
+// -------
+// Systems
+// -------
+
+pub fn update_player_flow_field(
+    level_walls: Res<LevelWalls>,
+    q_player: Query<(&Transform, &Parent), With<Player>>,
+    q_parents: Query<&Parent>,
+    mut flow_field: ResMut<PlayerFlowField>,
+) {
+    let Ok((player_transform, player_parent)) = q_player.get_single() else {
+        return;
+    };
+
+    // the player is placed in LDtk, its parent is a layer of its level
+    let Ok(level) = q_parents
+        .get(player_parent.get())
+        .map(|parent| parent.get())
+    else {
+        return;
+    };
+
+    let Some(grid) = level_walls.levels.get(&level) else {
+        if flow_field.level.is_some() {
+            *flow_field = PlayerFlowField::default();
+        }
+        return;
+    };
+
+    if flow_field.level != Some(level) || level_walls.is_changed() {
+        *flow_field = PlayerFlowField {
+            level: Some(level),
+            grid: grid.clone(),
+            ..default()
+        };
+    }
+
+    let target = flow_field
+        .grid
+        .cell_at(player_transform.translation.truncate());
+
+    // a running search is finished first, so the field keeps up with a moving player
+    if flow_field.search.is_none() && flow_field.target != Some(target) {
+        flow_field.start_search(target);
+    }
+
+    flow_field.continue_search(SEARCH_CELLS_PER_FRAME);
+}
+
+// ------
+// Plugin
+// ------
+
+pub struct EnemyPathfindingPlugin;
+
+impl Plugin for EnemyPathfindingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayerFlowField>().add_systems(
+            Update,
+            update_player_flow_field
+                .before(update_enemies_move_direction)
+                .run_if(in_state(GameState::GamePlay)),
+        );
+    }
+}
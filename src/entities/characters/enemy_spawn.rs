@@ -0,0 +1,203 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::ldtk::WallGrid;
+
+/// Enemies never spawn closer than this to the edge of the level.
+const LEVEL_MARGIN: f32 = 24.0;
+/// Half size of the area around a spawn position that must be free of walls.
+const WALL_CLEARANCE: f32 = 12.0;
+/// Enemies never spawn closer than this to the player.
+const MIN_PLAYER_DISTANCE: f32 = 96.0;
+/// How far past the edge of the camera the off-screen ring reaches.
+const RING_WIDTH: f32 = 64.0;
+/// How deep into the level edge spawns reach.
+const EDGE_DEPTH: f32 = 32.0;
+/// Enemies of a cluster spawn this close to its center.
+const CLUSTER_RADIUS: f32 = 24.0;
+/// Spawn points scatter their enemies this far.
+const SPAWN_POINT_RADIUS: f32 = 16.0;
+/// Candidates tried per strategy before falling back to the next one.
+const SPAWN_ATTEMPTS: usize = 32;
+
+// -----
+// Types
+// -----
+
+/// Where the enemies of a wave entry show up. Every strategy keeps off walls
+/// and away from the player, and falls back to the off-screen ring, then to
+/// anywhere in the level, when it can't find a free spot.
+#[derive(Clone, Copy, PartialEq, Debug, Default, Serialize, Deserialize)]
+pub enum SpawnStrategy {
+    /// Around the player, just outside of the camera's view.
+    #[default]
+    OffScreenRing,
+    /// Along the edges of the level.
+    LevelEdge,
+    /// At the `EnemySpawnPoint` entities placed in the LDtk level.
+    SpawnPoints,
+    /// Groups of `size` enemies close together, just outside of the camera's view.
+    Clusters { size: usize },
+}
+
+/// What spawn strategies know about the level, in level-local coordinates.
+pub struct SpawnArea<'a> {
+    pub size: Vec2,
+    pub walls: Option<&'a WallGrid>,
+    pub player: Vec2,
+    /// Part of the level seen by the camera, if there is one.
+    pub view: Option<Rect>,
+    pub spawn_points: &'a [Vec2],
+}
+
+impl SpawnArea<'_> {
+    /// Positions for up to `count` enemies, fewer if the level has no room left.
+    pub fn positions(&self, strategy: SpawnStrategy, count: u32, rng: &mut impl Rng) -> Vec<Vec2> {
+        let mut positions = Vec::with_capacity(count as usize);
+        let mut cluster_center = None;
+
+        for i in 0..count as usize {
+            let position = match strategy {
+                SpawnStrategy::Clusters { size } => {
+                    if i % size.max(1) == 0 {
+                        cluster_center = self.find(rng, |rng| Some(self.ring_candidate(rng)));
+                    }
+
+                    cluster_center.and_then(|center| {
+                        self.find(rng, |rng| {
+                            Some(center + random_in_circle(CLUSTER_RADIUS, rng))
+                        })
+                    })
+                }
+                _ => self.find(rng, |rng| self.candidate(strategy, rng)),
+            }
+            .or_else(|| self.find(rng, |rng| Some(self.ring_candidate(rng))))
+            .or_else(|| self.find(rng, |rng| Some(self.anywhere_candidate(rng))));
+
+            if let Some(position) = position {
+                positions.push(position);
+            }
+        }
+
+        positions
+    }
+
+    /// Tries candidates until one is free, gives up after `SPAWN_ATTEMPTS`.
+    fn find<R: Rng>(
+        &self,
+        rng: &mut R,
+        mut candidate: impl FnMut(&mut R) -> Option<Vec2>,
+    ) -> Option<Vec2> {
+        (0..SPAWN_ATTEMPTS)
+            .filter_map(|_| candidate(rng))
+            .find(|position| self.is_free(*position))
+    }
+
+    fn candidate(&self, strategy: SpawnStrategy, rng: &mut impl Rng) -> Option<Vec2> {
+        match strategy {
+            SpawnStrategy::OffScreenRing | SpawnStrategy::Clusters { .. } => {
+                Some(self.ring_candidate(rng))
+            }
+            SpawnStrategy::LevelEdge => Some(self.edge_candidate(rng)),
+            SpawnStrategy::SpawnPoints => self
+                .spawn_points
+                .choose(rng)
+                .map(|point| *point + random_in_circle(SPAWN_POINT_RADIUS, rng)),
+        }
+    }
+
+    fn ring_candidate(&self, rng: &mut impl Rng) -> Vec2 {
+        // headless runs have no camera, the ring then hugs the closest allowed distance
+        let view_radius = self
+            .view
+            .map(|view| view.half_size().length())
+            .unwrap_or(MIN_PLAYER_DISTANCE);
+
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let distance = view_radius + rng.gen_range(0.0..RING_WIDTH);
+
+        self.player + Vec2::from_angle(angle) * distance
+    }
+
+    fn edge_candidate(&self, rng: &mut impl Rng) -> Vec2 {
+        let depth = LEVEL_MARGIN + rng.gen_range(0.0..EDGE_DEPTH);
+        let x = rng.gen_range(0.0..self.size.x);
+        let y = rng.gen_range(0.0..self.size.y);
+
+        match rng.gen_range(0..4) {
+            0 => Vec2::new(x, depth),
+            1 => Vec2::new(x, self.size.y - depth),
+            2 => Vec2::new(depth, y),
+            _ => Vec2::new(self.size.x - depth, y),
+        }
+    }
+
+    fn anywhere_candidate(&self, rng: &mut impl Rng) -> Vec2 {
+        Vec2::new(
+            rng.gen_range(0.0..self.size.x),
+            rng.gen_range(0.0..self.size.y),
+        )
+    }
+
+    fn is_free(&self, position: Vec2) -> bool {
+        let inside_level = position.cmpge(Vec2::splat(LEVEL_MARGIN)).all()
+            && position.cmple(self.size - LEVEL_MARGIN).all();
+
+        if !inside_level || position.distance(self.player) < MIN_PLAYER_DISTANCE {
+            return false;
+        }
+
+        if let Some(view) = self.view {
+            if view.contains(position) {
+                return false;
+            }
+        }
+
+        let Some(walls) = self.walls else {
+            return true;
+        };
+
+        [
+            Vec2::ZERO,
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(-1.0, 1.0),
+            Vec2::new(1.0, 1.0),
+        ]
+        .iter()
+        .all(|corner| !walls.is_wall_at(position + *corner * WALL_CLEARANCE))
+    }
+}
+
+fn random_in_circle(radius: f32, rng: &mut impl Rng) -> Vec2 {
+    let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+    Vec2::from_angle(angle) * radius * rng.gen::<f32>().sqrt()
+}
+
+// ----------
+// Components
+// ----------
+
+/// Marks where `SpawnStrategy::SpawnPoints` spawns enemies.
+#[derive(Clone, Copy, Debug, Default, Component)]
+pub struct EnemySpawnPoint;
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct EnemySpawnPointBundle {
+    spawn_point: EnemySpawnPoint,
+}
+
+// ------
+// Plugin
+// ------
+
+pub struct EnemySpawnPlugin;
+
+impl Plugin for EnemySpawnPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_ldtk_entity::<EnemySpawnPointBundle>("EnemySpawnPoint");
+    }
+}
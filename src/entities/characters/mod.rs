@@ -1,9 +1,11 @@
 use bevy::prelude::*;
 
 pub mod enemy;
-pub mod mierda;
-pub mod pendejo;
-pub mod psychiatrist;
+pub mod enemy_ai;
+pub mod enemy_archetype;
+pub mod enemy_crowd;
+pub mod enemy_pathfinding;
+pub mod enemy_spawn;
 
 pub struct CharactersPlugin;
 
@@ -17,9 +19,10 @@ impl Plugin for CharactersPlugin {
 
         app.add_plugins((
             enemy::EnemyPlugin,
-            mierda::MierdaPlugin,
-            pendejo::PendejoPlugin,
-            psychiatrist::PsychiatristPlugin,
+            enemy_ai::EnemyAiPlugin,
+            enemy_crowd::EnemyCrowdPlugin,
+            enemy_pathfinding::EnemyPathfindingPlugin,
+            enemy_spawn::EnemySpawnPlugin,
         ));
     }
 }
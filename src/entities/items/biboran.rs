@@ -98,11 +98,50 @@ pub struct BiboranEffectTimer(pub Timer);
 #[derive(Resource, Default)]
 pub struct BiboranPrayer(Handle<AudioInstance>);
 
-#[allow(clippy::single_match)]
+/// How long the holy effect lasts after stepping over a biboran.
+pub const BIBORAN_EFFECT_DURATION: Duration = Duration::from_secs(14);
+
+// ------
+// Events
+// ------
+
+/// Starts the holy effect for `duration`, sent when a biboran is picked up
+/// or a saved run with an active effect is resumed.
+#[derive(Event, Clone)]
+pub struct ActivateBiboranEvent {
+    pub duration: Duration,
+}
+
 pub fn event_on_biboran_step_over(
     mut commands: Commands,
     mut er_item_step_over: EventReader<ItemStepOverEvent>,
     mut q_items: Query<(Entity, &Item)>,
+    mut ev_activate_biboran: EventWriter<ActivateBiboranEvent>,
+) {
+    for e in er_item_step_over.read() {
+        if e.item_type != ItemType::Biboran {
+            continue;
+        }
+
+        ev_activate_biboran.send(ActivateBiboranEvent {
+            duration: BIBORAN_EFFECT_DURATION,
+        });
+
+        for (e_biboran, item) in q_items.iter_mut() {
+            if e_biboran != e.entity {
+                continue;
+            }
+            if item.item_type != ItemType::Biboran {
+                continue;
+            }
+            commands.entity(e_biboran).despawn_recursive();
+        }
+    }
+}
+
+#[allow(clippy::single_match)]
+pub fn event_activate_biboran(
+    mut er_activate_biboran: EventReader<ActivateBiboranEvent>,
     mut q_player: Query<(Entity, &mut Player)>,
     mut q_biboran_animations: Query<(&mut Visibility, &BiboranSprite)>, // mut q_ui_healthbar: Query<(Entity, &mut Style, &ui::UIPlayerHealth)>,
     mut biboran_timer: ResMut<BiboranTimer>,
@@ -112,13 +151,9 @@ pub fn event_on_biboran_step_over(
     audio: Res<BiboranPrayer>,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
 ) {
-    for e in er_item_step_over.read() {
-        if e.item_type != ItemType::Biboran {
-            continue;
-        }
-
+    for e in er_activate_biboran.read() {
         for (_, mut _player) in q_player.iter_mut() {
-            biboran_timer.0 = Timer::new(Duration::from_secs(14), TimerMode::Once);
+            biboran_timer.0 = Timer::new(e.duration, TimerMode::Once);
             biboran_effect_timer.0 = Timer::new(Duration::from_secs(1), TimerMode::Repeating);
 
             for (mut v, _) in q_biboran_animations.iter_mut() {
@@ -139,16 +174,6 @@ pub fn event_on_biboran_step_over(
         for (mut player, _) in &mut players {
             player.play(animations.0.clone_weak()).repeat();
         }
-
-        for (e_biboran, item) in q_items.iter_mut() {
-            if e_biboran != e.entity {
-                continue;
-            }
-            if item.item_type != ItemType::Biboran {
-                continue;
-            }
-            commands.entity(e_biboran).despawn_recursive();
-        }
     }
 }
 
@@ -385,13 +410,18 @@ impl Plugin for BiboranPlugin {
             .init_resource::<BiboranTimer>()
             .init_resource::<BiboranEffectTimer>()
             .init_resource::<BiboranPrayer>()
+            .add_event::<ActivateBiboranEvent>()
             .add_systems(OnEnter(GameState::GamePlay), setup_biboran_prayer)
             .add_systems(Startup, setup_biboran_scene)
             .add_systems(Update, biboran_holy_effect)
             // Event Handlers
             .add_systems(
                 Update,
-                (event_on_biboran_step_over, ineject_biboran_render_sprite),
+                (
+                    event_on_biboran_step_over,
+                    event_activate_biboran,
+                    ineject_biboran_render_sprite,
+                ),
             );
     }
 }
@@ -2,20 +2,23 @@ use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     entities::player::Player,
     loading::load_texture_atlas,
     physics::ColliderBundle,
-    sprites::{BIBORAN_ASSET_SHEET, PIZZA_ASSET_SHEET},
+    rng::{GameRng, GameRngSet},
+    sprites::{BIBORAN_ASSET_SHEET, PIZZA_ASSET_SHEET, PX_ASSET_SHEET},
     utils::*,
 };
 
-#[derive(Clone, Copy, PartialEq, Debug, Default, Component, Reflect)]
+#[derive(Clone, Copy, PartialEq, Debug, Default, Component, Reflect, Serialize, Deserialize)]
 pub enum ItemType {
     #[default]
     Pizza,
     Biboran,
+    XpGem,
 }
 
 #[derive(Clone, PartialEq, Debug, Default, Component, Reflect)]
@@ -61,6 +64,12 @@ pub fn create_item_bundle(
             rotation_constraints,
             ..Default::default()
         },
+        ItemType::XpGem => ColliderBundle {
+            collider: Collider::ball(4.),
+            rigid_body: RigidBody::Fixed,
+            rotation_constraints,
+            ..Default::default()
+        },
     };
 
     let atlas_handle = match item_type {
@@ -82,11 +91,29 @@ pub fn create_item_bundle(
             Vec2::ONE * 32.,
             texture_atlasses,
         ),
+        ItemType::XpGem => load_texture_atlas(
+            PX_ASSET_SHEET.to_string(),
+            asset_server,
+            1,
+            1,
+            None,
+            Vec2::ONE,
+            texture_atlasses,
+        ),
+    };
+
+    let sprite = match item_type {
+        ItemType::XpGem => TextureAtlasSprite {
+            color: Color::CYAN,
+            custom_size: Some(Vec2::ONE * 6.),
+            ..TextureAtlasSprite::new(0)
+        },
+        _ => TextureAtlasSprite::new(0),
     };
 
     let sprite_bundle = SpriteSheetBundle {
         texture_atlas: atlas_handle,
-        sprite: TextureAtlasSprite::new(0),
+        sprite,
         ..default()
     };
 
@@ -121,6 +148,31 @@ pub struct SpawnItemEvent {
 // Event Handlers
 // --------------
 
+/// Spawns an item as a copy of the LDtk dummy entity `dummy`, as a child of the dummy's `level`.
+pub fn spawn_item_from_dummy(
+    commands: &mut Commands,
+    dummy: Entity,
+    level: Entity,
+    item_type: ItemType,
+    transform: Transform,
+) -> Entity {
+    let new_entity = commands
+        .spawn(Item {
+            is_dummy: false,
+            item_type,
+        })
+        .set_parent(level)
+        .id();
+
+    commands.add(CloneEntity {
+        source: dummy,
+        destination: new_entity,
+    });
+
+    commands.entity(new_entity).insert(transform);
+    new_entity
+}
+
 pub fn event_spawn_item(
     mut commands: Commands,
     mut ev_spawn_item: EventReader<SpawnItemEvent>,
@@ -130,12 +182,12 @@ pub fn event_spawn_item(
     project_assets: Res<Assets<LdtkProject>>,
     q_items: Query<(Entity, &Parent, &Item)>,
     q_player_query: Query<(Entity, &Transform, &Player)>,
+    mut rng: ResMut<GameRng>,
 ) {
     if q_player_query.iter().count() == 0 {
         return;
     }
 
-    let mut rng = rand::thread_rng();
     let player_translation = q_player_query.single().1.translation;
 
     for ev_spawn in ev_spawn_item.read() {
@@ -161,16 +213,6 @@ pub fn event_spawn_item(
                             continue;
                         }
 
-                        let item_parent = item_parent.get();
-
-                        let mut parent = commands.entity(item_parent);
-
-                        let mut new_entity: Option<Entity> = None;
-                        parent.with_children(|cm| {
-                            let ne = cm.spawn_empty().id();
-                            new_entity = Some(ne);
-                        });
-
                         // generate random position
 
                         let mut offset_position = Vec3::new(0.0, 0.0, 0.);
@@ -194,18 +236,13 @@ pub fn event_spawn_item(
                         let transform =
                             Transform::from_translation(item_position).with_scale(Vec3::ONE * 0.5);
 
-                        let new_entity = new_entity.unwrap();
-                        commands.entity(new_entity).insert(Item {
-                            is_dummy: false,
-                            item_type: ev_spawn.item_type,
-                        });
-
-                        commands.add(CloneEntity {
-                            source: item_entity,
-                            destination: new_entity,
-                        });
-
-                        commands.entity(new_entity).insert(transform);
+                        spawn_item_from_dummy(
+                            &mut commands,
+                            item_entity,
+                            item_parent.get(),
+                            ev_spawn.item_type,
+                            transform,
+                        );
                         break;
                     }
                 }
@@ -264,6 +301,12 @@ impl Plugin for ItemPlugin {
             .add_event::<SpawnItemEvent>()
             .add_event::<ItemStepOverEvent>()
             // Event Handlers
-            .add_systems(Update, (handle_player_item_collision, event_spawn_item));
+            .add_systems(
+                Update,
+                (
+                    handle_player_item_collision,
+                    event_spawn_item.in_set(GameRngSet::SpawnItems),
+                ),
+            );
     }
 }
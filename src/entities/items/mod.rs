@@ -3,6 +3,7 @@ use bevy::prelude::*;
 pub mod biboran;
 pub mod item;
 pub mod pizza;
+pub mod xp_gem;
 
 pub struct ItemsPlugin;
 
@@ -14,6 +15,11 @@ impl Plugin for ItemsPlugin {
             wr.register::<item::Item>();
         }
 
-        app.add_plugins((item::ItemPlugin, pizza::PizzaPlugin, biboran::BiboranPlugin));
+        app.add_plugins((
+            item::ItemPlugin,
+            pizza::PizzaPlugin,
+            biboran::BiboranPlugin,
+            xp_gem::XpGemPlugin,
+        ));
     }
 }
@@ -0,0 +1,148 @@
+use bevy::prelude::*;
+
+use crate::{
+    entities::player::Player,
+    gameplay::experience::{Experience, Perks},
+    GameState,
+};
+
+use super::item::{create_item_bundle, Item, ItemStepOverEvent, ItemType};
+
+/// Speed at which gems inside the pickup radius fly towards the player.
+const XP_GEM_ATTRACT_SPEED: f32 = 300.0;
+
+// ----------
+// Components
+// ----------
+
+#[derive(Clone, Copy, PartialEq, Debug, Default, Component)]
+pub struct XpGem {
+    pub xp: u32,
+}
+
+// ------
+// Events
+// ------
+
+/// Drops a gem worth `xp` at `transform`, relative to `level`.
+#[derive(Event, Clone)]
+pub struct SpawnXpGemEvent {
+    pub level: Entity,
+    pub transform: Transform,
+    pub xp: u32,
+}
+
+// --------------
+// Event Handlers
+// --------------
+
+/// Gems aren't placed in LDtk, so unlike other items they are built from
+/// scratch instead of copied from a dummy.
+pub fn spawn_xp_gem(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    texture_atlasses: &mut Assets<TextureAtlas>,
+    level: Entity,
+    transform: Transform,
+    xp: u32,
+) -> Entity {
+    let mut bundle = create_item_bundle(asset_server, texture_atlasses, false, ItemType::XpGem);
+    bundle.sprite_bundle.transform = transform;
+
+    commands
+        .spawn((bundle, XpGem { xp }, Name::new("xp gem")))
+        .set_parent(level)
+        .id()
+}
+
+pub fn event_spawn_xp_gem(
+    mut commands: Commands,
+    mut ev_spawn_xp_gem: EventReader<SpawnXpGemEvent>,
+    asset_server: Res<AssetServer>,
+    mut texture_atlasses: ResMut<Assets<TextureAtlas>>,
+) {
+    for ev in ev_spawn_xp_gem.read() {
+        if commands.get_entity(ev.level).is_none() {
+            continue;
+        }
+
+        spawn_xp_gem(
+            &mut commands,
+            &asset_server,
+            &mut texture_atlasses,
+            ev.level,
+            ev.transform,
+            ev.xp,
+        );
+    }
+}
+
+pub fn event_on_xp_gem_step_over(
+    mut commands: Commands,
+    mut er_item_step_over: EventReader<ItemStepOverEvent>,
+    q_xp_gems: Query<&XpGem, With<Item>>,
+    mut experience: ResMut<Experience>,
+) {
+    for e in er_item_step_over.read() {
+        if e.item_type != ItemType::XpGem {
+            continue;
+        }
+
+        let Ok(xp_gem) = q_xp_gems.get(e.entity) else {
+            continue;
+        };
+
+        experience.add_xp(xp_gem.xp);
+        commands.entity(e.entity).despawn_recursive();
+    }
+}
+
+// -------
+// Systems
+// -------
+
+pub fn attract_xp_gems(
+    time: Res<Time>,
+    perks: Res<Perks>,
+    q_player: Query<&Transform, With<Player>>,
+    mut q_xp_gems: Query<&mut Transform, (With<XpGem>, Without<Player>)>,
+) {
+    let Ok(player_transform) = q_player.get_single() else {
+        return;
+    };
+
+    let pickup_radius = perks.pickup_radius();
+    let player_position = player_transform.translation.truncate();
+
+    for mut gem_transform in q_xp_gems.iter_mut() {
+        let to_player = player_position - gem_transform.translation.truncate();
+        if to_player.length() > pickup_radius {
+            continue;
+        }
+
+        let step = to_player.normalize_or_zero() * XP_GEM_ATTRACT_SPEED * time.delta_seconds();
+        gem_transform.translation += step.clamp_length_max(to_player.length()).extend(0.0);
+    }
+}
+
+// ------
+// Plugin
+// ------
+
+pub struct XpGemPlugin;
+
+impl Plugin for XpGemPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<SpawnXpGemEvent>()
+            // Event Handlers
+            .add_systems(
+                Update,
+                (
+                    event_spawn_xp_gem,
+                    event_on_xp_gem_step_over,
+                    attract_xp_gems,
+                )
+                    .run_if(in_state(GameState::GamePlay)),
+            );
+    }
+}
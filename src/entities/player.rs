@@ -6,19 +6,45 @@ use bevy_particle_systems::*;
 use bevy_rapier2d::prelude::*;
 
 use crate::{
-    gameover::GameOverEvent, loading::load_texture_atlas,
-    physics::ColliderBundle, sprites::*, ui::UIPlayerHealth, AudioAssets, GameState,
+    gameover::GameOverEvent,
+    gameplay::{experience::Perks, scoring::RunStats},
+    loading::load_texture_atlas,
+    physics::ColliderBundle,
+    sprites::*,
+    ui::UIPlayerHealth,
+    AudioAssets, GameState,
 };
 
 use super::characters::enemy::{Enemy, EnemyHitEvent};
+use super::characters::enemy_archetype::{EnemyArchetypeId, EnemyArchetypeTable};
+use super::text_indicator::SpawnTextIndicatorEvent;
+use super::weapons::machete::MacheteSlashEvent;
+use super::weapons::weapon::{WeaponInventory, WeaponKind};
+use super::weapons::weapon_table::WeaponTable;
 
 // --------
 // Entities
 // --------
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component, Reflect)]
+/// Time the player can't be hurt again after a hit.
+const INVULNERABILITY_SECS: f32 = 0.5;
+
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component, Reflect)]
 pub struct Player {
     pub health: u16,
+    /// Taken off the damage of every hit, on top of the armor perk.
+    pub armor: u16,
+    /// Runs while the player is invulnerable after a hit.
+    pub hit_at: Option<Timer>,
+}
+
+/// Enemies touching the player, kept up to date from collision events.
+#[derive(Clone, Debug, Default, Component, Reflect)]
+pub struct EnemyContacts {
+    pub enemies: Vec<Entity>,
+    /// Its duration follows the archetype table's `contact_interval`, it is set
+    /// in `tick_contact_damage`.
+    pub timer: Timer,
 }
 
 #[derive(Default, Bundle)]
@@ -31,6 +57,8 @@ pub struct PlayerBundle {
     pub collider_bundle: ColliderBundle,
     pub active_events: ActiveEvents,
     pub name: Name,
+    pub weapon_inventory: WeaponInventory,
+    pub enemy_contacts: EnemyContacts,
 }
 
 // ----
@@ -82,12 +110,17 @@ impl LdtkEntity for PlayerBundle {
             sprite_bundle,
             collider_bundle,
             active_events: ActiveEvents::COLLISION_EVENTS,
-            player: Player { health: 100 },
+            player: Player {
+                health: 100,
+                armor: 0,
+                hit_at: None,
+            },
             animated_character_sprite: AnimatedCharacterSprite {
                 animated_character_type: AnimatedCharacterType::Player,
             },
             name: Name::new("Player"),
-            // machete_timer: MacheteTimer(Timer::from_seconds(1.0, TimerMode::Repeating)),
+            weapon_inventory: WeaponInventory::starting(),
+            enemy_contacts: EnemyContacts::default(),
         }
     }
 }
@@ -101,9 +134,21 @@ pub struct PlayerAttackEvent {
     pub entity: Entity,
 }
 
+/// What hurt the player.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DamageSource {
+    /// Touching an enemy.
+    Contact(EnemyArchetypeId),
+    /// A projectile shot by an enemy.
+    Projectile(EnemyArchetypeId),
+}
+
 #[derive(Event, Clone)]
 pub struct PlayerHitEvent {
     pub entity: Entity,
+    /// Damage before armor.
+    pub damage: u16,
+    pub source: DamageSource,
 }
 
 // --------------
@@ -114,38 +159,58 @@ pub fn event_player_attack(
     mut commands: Commands,
     mut ev_player_attack: EventReader<PlayerAttackEvent>,
     mut ev_enemy_hit: EventWriter<EnemyHitEvent>,
-    mut q_player: Query<(Entity, &Transform, &CharacterAnimation), With<Player>>,
+    mut ev_machete_slash: EventWriter<MacheteSlashEvent>,
+    mut q_player: Query<(Entity, &Transform, &CharacterAnimation, &WeaponInventory), With<Player>>,
     mut q_enemies: Query<(Entity, &Transform, &mut Enemy)>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    weapon_table: Res<WeaponTable>,
 ) {
     for ev in ev_player_attack.read() {
         if commands.get_entity(ev.entity).is_none() {
             continue;
         }
 
-        let (_, transform, char_animation) = q_player.get_mut(ev.entity).unwrap();
+        let (_, transform, char_animation, inventory) = q_player.get_mut(ev.entity).unwrap();
+
+        let Some(machete) = inventory.get(WeaponKind::Machete) else {
+            continue;
+        };
+        let stats = machete.stats(&weapon_table);
 
         let player_position = transform.translation;
-        let _player_orientation = char_animation.direction;
+        let player_orientation = char_animation.direction.vec();
+        let half_arc = stats.arc.to_radians() / 2.0;
 
         audio.play(audio_assets.slash.clone());
 
-        // find all mierdas in range
+        // find all mierdas in range, in front of the player
         for (entity, mierda_transform, _) in q_enemies.iter_mut().filter(|(_, _, m)| !m.is_dummy) {
             let mierda_position = mierda_transform.translation;
 
             let distance = player_position.distance(mierda_position);
 
-            if distance >= 45. {
+            if distance >= stats.area {
+                continue;
+            }
+
+            let to_mierda = (mierda_position - player_position).truncate();
+            if stats.arc < 360.0 && player_orientation.angle_between(to_mierda).abs() > half_arc {
                 continue;
             }
 
             ev_enemy_hit.send(EnemyHitEvent {
                 entity,
-                damage: 100,
+                damage: stats.damage,
             });
         }
+
+        ev_machete_slash.send(MacheteSlashEvent {
+            entity: ev.entity,
+            direction: player_orientation,
+            area: stats.area,
+            arc: stats.arc,
+        });
     }
 }
 
@@ -153,11 +218,14 @@ pub fn event_player_hit(
     mut commands: Commands,
     mut ev_player_hit_reader: EventReader<PlayerHitEvent>,
     mut ev_game_over: EventWriter<GameOverEvent>,
+    mut ev_spawn_text_indicator: EventWriter<SpawnTextIndicatorEvent>,
     mut q_player: Query<(Entity, &GlobalTransform, &mut Player)>,
     mut q_ui_healthbar: Query<(Entity, &mut Style, &UIPlayerHealth)>,
     asset_server: Res<AssetServer>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
+    perks: Res<Perks>,
+    mut run_stats: ResMut<RunStats>,
 ) {
     for ev in ev_player_hit_reader.read() {
         if commands.get_entity(ev.entity).is_none() {
@@ -166,6 +234,20 @@ pub fn event_player_hit(
 
         let (_, player_transform, mut player) = q_player.get_mut(ev.entity).unwrap();
 
+        // invulnerability frames, hits landing at the same time count once
+        if player.hit_at.is_some() {
+            continue;
+        }
+        player.hit_at = Some(Timer::from_seconds(INVULNERABILITY_SECS, TimerMode::Once));
+
+        let armor = player.armor + perks.armor();
+        let damage = ev.damage.saturating_sub(armor).max(1);
+
+        ev_spawn_text_indicator.send(SpawnTextIndicatorEvent {
+            text: format!("-{}", damage),
+            entity: ev.entity,
+        });
+
         commands.spawn((
             ParticleSystemBundle {
                 transform: (*player_transform).into(),
@@ -193,16 +275,42 @@ pub fn event_player_hit(
         ));
 
         audio.play(audio_assets.hurt.clone()).with_volume(0.5);
+        run_stats.damage_taken += damage as u32;
+
+        player.health = player.health.saturating_sub(damage);
+
+        for (_, mut style, _) in q_ui_healthbar.iter_mut() {
+            style.width = Val::Percent(player.health as f32);
+        }
 
         if player.health == 0 {
             ev_game_over.send(GameOverEvent);
+        }
+    }
+}
+
+// -------
+// Systems
+// -------
+
+/// Counts down the invulnerability after a hit, the player blinks meanwhile.
+pub fn update_player_invulnerability(
+    time: Res<Time>,
+    mut q_player: Query<(&mut Player, &mut TextureAtlasSprite)>,
+) {
+    for (mut player, mut sprite) in q_player.iter_mut() {
+        let Some(timer) = player.hit_at.as_mut() else {
             continue;
-        } else {
-            player.health -= 1;
+        };
 
-            for (_, mut style, _) in q_ui_healthbar.iter_mut() {
-                style.width = Val::Percent(player.health as f32);
-            }
+        timer.tick(time.delta());
+
+        if timer.finished() {
+            player.hit_at = None;
+            sprite.color.set_a(1.0);
+        } else {
+            let blink = (timer.elapsed_secs() * 20.0) as u32 % 2 == 0;
+            sprite.color.set_a(if blink { 0.3 } else { 1.0 });
         }
     }
 }
@@ -213,33 +321,105 @@ pub fn event_player_hit(
 
 pub fn handle_player_enemy_collisions(
     mut collision_events: EventReader<CollisionEvent>,
-    q_player: Query<(Entity, &mut Player)>,
+    mut q_player: Query<(Entity, &mut EnemyContacts), With<Player>>,
     q_enemies: Query<(Entity, &Enemy)>,
+    archetypes: Res<EnemyArchetypeTable>,
     mut ev_player_hit: EventWriter<PlayerHitEvent>,
 ) {
     for event in collision_events.read() {
-        if let CollisionEvent::Started(e1, e2, _) = event {
-            let contact_1_player = q_player.get(*e1);
-            let contact_2_player = q_player.get(*e2);
-            let is_contact_player = contact_1_player.is_ok() || contact_2_player.is_ok();
+        match event {
+            CollisionEvent::Started(e1, e2, _) => {
+                let is_contact_player = q_player.contains(*e1) || q_player.contains(*e2);
+
+                let contact_1_enemy = q_enemies.get(*e1);
+                let contact_2_enemy = q_enemies.get(*e2);
+                let is_contact_enemy = contact_1_enemy.is_ok() || contact_2_enemy.is_ok();
+
+                if !(is_contact_player && is_contact_enemy) {
+                    continue;
+                }
+
+                let (enemy_entity, enemy) = match contact_1_enemy.is_ok() {
+                    true => contact_1_enemy.unwrap(),
+                    false => contact_2_enemy.unwrap(),
+                };
+
+                let player_entity = if enemy_entity == *e1 { *e2 } else { *e1 };
+                let (_, mut contacts) = q_player.get_mut(player_entity).unwrap();
+                contacts.enemies.push(enemy_entity);
+
+                ev_player_hit.send(PlayerHitEvent {
+                    entity: player_entity,
+                    damage: contact_damage_of(&archetypes, enemy),
+                    source: DamageSource::Contact(enemy.archetype.clone()),
+                });
+            }
+            CollisionEvent::Stopped(e1, e2, _) => {
+                // the enemy may be gone already, drop whichever side isn't the player
+                for (player_entity, mut contacts) in q_player.iter_mut() {
+                    if player_entity == *e1 || player_entity == *e2 {
+                        contacts.enemies.retain(|e| e != e1 && e != e2);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Contact damage of `enemy`, 0 if its archetype is gone.
+fn contact_damage_of(archetypes: &EnemyArchetypeTable, enemy: &Enemy) -> u16 {
+    archetypes
+        .get(&enemy.archetype)
+        .map_or(0, |archetype| archetype.contact_damage)
+}
 
-            let contact_1_enemy = q_enemies.get(*e1);
-            let contact_2_enemy = q_enemies.get(*e2);
-            let is_contact_enemy = contact_1_enemy.is_ok() || contact_2_enemy.is_ok();
+/// Hurts the player again every `contact_interval` while enemies stay in
+/// touch, the strongest one deals the damage. A tick that lands during the
+/// invulnerability frames waits for them to end instead of being swallowed.
+pub fn tick_contact_damage(
+    time: Res<Time>,
+    mut q_player: Query<(Entity, &Player, &mut EnemyContacts)>,
+    q_enemies: Query<&Enemy>,
+    archetypes: Res<EnemyArchetypeTable>,
+    mut ev_player_hit: EventWriter<PlayerHitEvent>,
+) {
+    for (player_entity, player, mut contacts) in q_player.iter_mut() {
+        contacts.enemies.retain(|e| {
+            q_enemies
+                .get(*e)
+                .is_ok_and(|enemy| !enemy.marked_for_despawn)
+        });
+
+        if contacts.enemies.is_empty() {
+            contacts.timer.reset();
+            continue;
+        }
 
-            if !(is_contact_player && is_contact_enemy) {
-                continue;
-            }
+        if contacts.timer.duration() != archetypes.contact_interval {
+            contacts.timer.set_duration(archetypes.contact_interval);
+        }
 
-            let player_entity = match contact_1_player.is_ok() {
-                true => contact_1_player.unwrap().0,
-                false => contact_2_player.unwrap().0,
-            };
+        contacts.timer.tick(time.delta());
 
-            ev_player_hit.send(PlayerHitEvent {
-                entity: player_entity,
-            });
+        if !contacts.timer.finished() || player.hit_at.is_some() {
+            continue;
         }
+        contacts.timer.reset();
+
+        let Some(enemy) = contacts
+            .enemies
+            .iter()
+            .filter_map(|e| q_enemies.get(*e).ok())
+            .max_by_key(|enemy| contact_damage_of(&archetypes, enemy))
+        else {
+            continue;
+        };
+
+        ev_player_hit.send(PlayerHitEvent {
+            entity: player_entity,
+            damage: contact_damage_of(&archetypes, enemy),
+            source: DamageSource::Contact(enemy.archetype.clone()),
+        });
     }
 }
 
@@ -260,7 +440,12 @@ impl Plugin for PlayerPlugin {
                 Update,
                 (
                     event_player_attack,
-                    event_player_hit,
+                    (
+                        update_player_invulnerability,
+                        tick_contact_damage,
+                        event_player_hit,
+                    )
+                        .chain(),
                     handle_player_enemy_collisions,
                     // handle_machete_attack,
                 )
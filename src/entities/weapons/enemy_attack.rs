@@ -0,0 +1,203 @@
+use std::f32::consts::TAU;
+use std::time::Duration;
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::{BoxedFuture, HashMap};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::entities::characters::enemy_archetype::EnemyArchetypeId;
+use crate::gameplay::level_waves::deserialize_secs;
+use crate::loading::WeaponAssets;
+
+// -----
+// Asset
+// -----
+
+/// How the projectiles of one volley are laid out.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ShotPattern {
+    /// One projectile at the player.
+    Aimed,
+    /// `count` projectiles fanned `angle` degrees apart around the player's direction.
+    Spread { count: u32, angle: f32 },
+    /// `count` projectiles evenly around the enemy.
+    Ring { count: u32 },
+    /// A ring that turns by `step` degrees after each volley.
+    Spiral { count: u32, step: f32 },
+}
+
+impl ShotPattern {
+    /// Directions of the projectiles of the `volley`th volley, `aim` points at the player.
+    pub fn directions(&self, aim: Vec2, volley: u32) -> Vec<Vec2> {
+        let aim_angle = aim.y.atan2(aim.x);
+
+        let angles: Vec<f32> = match *self {
+            ShotPattern::Aimed => vec![aim_angle],
+            ShotPattern::Spread { count, angle } => (0..count)
+                .map(|i| aim_angle + angle.to_radians() * (i as f32 - (count as f32 - 1.0) / 2.0))
+                .collect(),
+            ShotPattern::Ring { count } => (0..count)
+                .map(|i| aim_angle + TAU * i as f32 / count as f32)
+                .collect(),
+            ShotPattern::Spiral { count, step } => (0..count)
+                .map(|i| TAU * i as f32 / count as f32 + step.to_radians() * volley as f32)
+                .collect(),
+        };
+
+        angles
+            .into_iter()
+            .map(|angle| Vec2::new(angle.cos(), angle.sin()))
+            .collect()
+    }
+
+    fn count(&self) -> u32 {
+        match *self {
+            ShotPattern::Aimed => 1,
+            ShotPattern::Spread { count, .. }
+            | ShotPattern::Ring { count }
+            | ShotPattern::Spiral { count, .. } => count,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ProjectileDefinition {
+    /// Path of the sprite, relative to `assets`.
+    pub sprite: String,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// Radius of the collider, in pixels.
+    pub radius: f32,
+    pub speed: f32,
+    #[serde(deserialize_with = "deserialize_secs")]
+    pub lifetime: Duration,
+    /// Damage dealt to the player, before armor.
+    pub damage: u16,
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+/// Ranged attack of an enemy type.
+#[derive(Deserialize, Clone, Debug)]
+pub struct EnemyAttack {
+    #[serde(deserialize_with = "deserialize_secs")]
+    pub cooldown: Duration,
+    /// The enemy only shoots while the player is closer than this.
+    pub range: f32,
+    pub pattern: ShotPattern,
+    pub projectile: ProjectileDefinition,
+}
+
+/// Ranged attacks of the enemy archetypes that have one, loaded from
+/// `assets/weapons/enemy.attacks.ron`.
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, Debug)]
+pub struct EnemyAttackTable {
+    pub attacks: HashMap<EnemyArchetypeId, EnemyAttack>,
+}
+
+impl EnemyAttackTable {
+    fn validate(&self) -> Result<(), String> {
+        for (id, attack) in self.attacks.iter() {
+            if attack.cooldown.is_zero() {
+                return Err(format!("{} attack has zero cooldown", id));
+            }
+            if attack.pattern.count() == 0 {
+                return Err(format!("{} attack shoots no projectiles", id));
+            }
+            if attack.projectile.speed <= 0.0 || attack.projectile.radius <= 0.0 {
+                return Err(format!(
+                    "{} projectile needs a positive speed and radius",
+                    id
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ------
+// Loader
+// ------
+
+#[derive(Debug, Error)]
+pub enum EnemyAttackTableLoaderError {
+    #[error("could not read enemy attack table: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse enemy attack table: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid enemy attack table: {0}")]
+    Invalid(String),
+}
+
+#[derive(Default)]
+pub struct EnemyAttackTableLoader;
+
+impl AssetLoader for EnemyAttackTableLoader {
+    type Asset = EnemyAttackTable;
+    type Settings = ();
+    type Error = EnemyAttackTableLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let attack_table = ron::de::from_bytes::<EnemyAttackTable>(&bytes)?;
+            attack_table
+                .validate()
+                .map_err(EnemyAttackTableLoaderError::Invalid)?;
+
+            Ok(attack_table)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["attacks.ron"]
+    }
+}
+
+// -------
+// Systems
+// -------
+
+pub fn insert_enemy_attack_table(
+    mut commands: Commands,
+    weapon_assets: Res<WeaponAssets>,
+    attack_tables: Res<Assets<EnemyAttackTable>>,
+) {
+    let attack_table = attack_tables
+        .get(&weapon_assets.enemy_attacks)
+        .expect("enemy attack table is loaded with the other assets");
+
+    commands.insert_resource(attack_table.clone());
+}
+
+/// Picks up edits of the enemy attack table while the game runs.
+pub fn hot_reload_enemy_attack_table(
+    mut er_attack_table: EventReader<AssetEvent<EnemyAttackTable>>,
+    attack_tables: Res<Assets<EnemyAttackTable>>,
+    mut attack_table: ResMut<EnemyAttackTable>,
+) {
+    for event in er_attack_table.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+
+        let Some(reloaded) = attack_tables.get(*id) else {
+            continue;
+        };
+
+        *attack_table = reloaded.clone();
+        info!("enemy attack table reloaded");
+    }
+}
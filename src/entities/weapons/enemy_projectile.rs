@@ -0,0 +1,269 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::entities::characters::enemy::Enemy;
+use crate::entities::characters::enemy_archetype::{EnemyArchetypeId, EnemyArchetypeTable};
+use crate::entities::player::{DamageSource, Player, PlayerHitEvent};
+use crate::ldtk::WallCollider;
+use crate::physics::ColliderBundle;
+use crate::GameState;
+
+use super::enemy_attack::{
+    hot_reload_enemy_attack_table, insert_enemy_attack_table, EnemyAttackTable,
+    EnemyAttackTableLoader,
+};
+
+// ----------
+// Components
+// ----------
+
+/// Put on enemies whose archetype has a ranged attack in the enemy attack table.
+#[derive(Component, Clone, Default)]
+pub struct EnemyRangedAttack {
+    pub timer: Timer,
+    /// Volleys shot so far, turns spiral patterns.
+    pub volley: u32,
+}
+
+#[derive(Component, Clone, Default)]
+pub struct EnemyProjectile {
+    pub damage: u16,
+    /// Archetype of the enemy that shot it.
+    pub archetype: EnemyArchetypeId,
+}
+
+#[derive(Component, Clone, Default)]
+pub struct EnemyProjectileDespawnTimer(pub Timer);
+
+// ------
+// Events
+// ------
+
+#[derive(Event, Clone, Copy)]
+pub struct EnemyShootEvent {
+    pub entity: Entity,
+}
+
+// -------
+// Systems
+// -------
+
+fn inject_enemy_ranged_attacks(
+    mut commands: Commands,
+    q_enemies: Query<(Entity, &Enemy), Added<Enemy>>,
+    attack_table: Res<EnemyAttackTable>,
+) {
+    for (entity, enemy) in q_enemies.iter().filter(|(_, e)| !e.is_dummy) {
+        let Some(attack) = attack_table.attacks.get(&enemy.archetype) else {
+            continue;
+        };
+
+        commands.entity(entity).insert(EnemyRangedAttack {
+            timer: Timer::new(attack.cooldown, TimerMode::Repeating),
+            volley: 0,
+        });
+    }
+}
+
+fn tick_enemy_ranged_attacks(
+    time: Res<Time>,
+    attack_table: Res<EnemyAttackTable>,
+    q_players: Query<&Transform, With<Player>>,
+    mut q_enemies: Query<(Entity, &Transform, &Enemy, &mut EnemyRangedAttack), Without<Player>>,
+    mut ev_enemy_shoot: EventWriter<EnemyShootEvent>,
+) {
+    let Ok(player_transform) = q_players.get_single() else {
+        return;
+    };
+
+    for (entity, transform, enemy, mut ranged_attack) in q_enemies.iter_mut() {
+        let Some(attack) = attack_table.attacks.get(&enemy.archetype) else {
+            continue;
+        };
+
+        // the table can be reloaded while the game runs
+        if ranged_attack.timer.duration() != attack.cooldown {
+            ranged_attack.timer.set_duration(attack.cooldown);
+        }
+
+        ranged_attack.timer.tick(time.delta());
+
+        let distance = transform.translation.distance(player_transform.translation);
+        if ranged_attack.timer.just_finished() && distance < attack.range {
+            ev_enemy_shoot.send(EnemyShootEvent { entity });
+        }
+    }
+}
+
+fn handle_enemy_shoot_event(
+    mut commands: Commands,
+    mut ev_enemy_shoot: EventReader<EnemyShootEvent>,
+    q_players: Query<&Transform, With<Player>>,
+    mut q_enemies: Query<(&Parent, &Transform, &Enemy, &mut EnemyRangedAttack), Without<Player>>,
+    attack_table: Res<EnemyAttackTable>,
+    asset_server: Res<AssetServer>,
+) {
+    let Ok(player_transform) = q_players.get_single() else {
+        return;
+    };
+
+    for ev in ev_enemy_shoot.read() {
+        let Ok((parent, transform, enemy, mut ranged_attack)) = q_enemies.get_mut(ev.entity) else {
+            continue;
+        };
+
+        let Some(attack) = attack_table.attacks.get(&enemy.archetype) else {
+            continue;
+        };
+
+        let aim = (player_transform.translation - transform.translation).truncate();
+        let directions = attack.pattern.directions(aim, ranged_attack.volley);
+        ranged_attack.volley += 1;
+
+        let projectile = &attack.projectile;
+        let texture = asset_server.load(&projectile.sprite);
+
+        commands.entity(parent.get()).with_children(|parent| {
+            for direction in directions {
+                parent.spawn((
+                    SpriteBundle {
+                        texture: texture.clone(),
+                        transform: Transform {
+                            translation: transform.translation,
+                            rotation: Quat::from_rotation_z(direction.y.atan2(direction.x)),
+                            scale: Vec3::ONE * projectile.scale,
+                        },
+                        ..default()
+                    },
+                    EnemyProjectile {
+                        damage: projectile.damage,
+                        archetype: enemy.archetype.clone(),
+                    },
+                    EnemyProjectileDespawnTimer(Timer::new(projectile.lifetime, TimerMode::Once)),
+                    ColliderBundle {
+                        collider: Collider::ball(projectile.radius),
+                        rigid_body: RigidBody::Dynamic,
+                        rotation_constraints: LockedAxes::ROTATION_LOCKED,
+                        velocity: Velocity {
+                            linvel: projectile.speed * direction,
+                            angvel: 0.0,
+                        },
+                        ..default()
+                    },
+                    Sensor,
+                    ActiveEvents::COLLISION_EVENTS,
+                    Name::new("enemy projectile"),
+                    ZIndex::Local(202),
+                ));
+            }
+        });
+    }
+}
+
+fn handle_enemy_projectile_timers(
+    mut commands: Commands,
+    mut q_projectiles: Query<(Entity, &mut EnemyProjectileDespawnTimer)>,
+    time: Res<Time>,
+) {
+    for (entity, mut timer) in q_projectiles.iter_mut() {
+        timer.0.tick(time.delta());
+
+        if timer.0.just_finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// -------
+// Physics
+// -------
+
+pub fn handle_enemy_projectile_player_collisions(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    q_players: Query<Entity, With<Player>>,
+    q_walls: Query<(), With<WallCollider>>,
+    q_projectiles: Query<&EnemyProjectile>,
+    mut ev_player_hit: EventWriter<PlayerHitEvent>,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(e1, e2, _) = event else {
+            continue;
+        };
+
+        // projectiles are sensors, walls have to stop them here
+        match (q_projectiles.contains(*e1), q_projectiles.contains(*e2)) {
+            (true, _) if q_walls.contains(*e2) => {
+                commands.entity(*e1).despawn_recursive();
+                continue;
+            }
+            (_, true) if q_walls.contains(*e1) => {
+                commands.entity(*e2).despawn_recursive();
+                continue;
+            }
+            _ => {}
+        }
+
+        let (projectile_entity, player_entity) =
+            match (q_projectiles.contains(*e1), q_players.contains(*e2)) {
+                (true, true) => (*e1, *e2),
+                _ if q_projectiles.contains(*e2) && q_players.contains(*e1) => (*e2, *e1),
+                _ => continue,
+            };
+
+        let projectile = q_projectiles.get(projectile_entity).unwrap();
+
+        ev_player_hit.send(PlayerHitEvent {
+            entity: player_entity,
+            damage: projectile.damage,
+            source: DamageSource::Projectile(projectile.archetype.clone()),
+        });
+
+        commands.entity(projectile_entity).despawn_recursive();
+    }
+}
+
+/// Warns about attacks of archetypes missing from the archetype table, a typo
+/// in either table would otherwise silently disable the attack.
+fn check_enemy_attack_archetypes(
+    attack_table: Res<EnemyAttackTable>,
+    archetypes: Res<EnemyArchetypeTable>,
+) {
+    for id in attack_table.attacks.keys() {
+        if archetypes.get(id).is_none() {
+            warn!("enemy attack of {} has no enemy archetype", id);
+        }
+    }
+}
+
+// ------
+// Plugin
+// ------
+
+pub struct EnemyProjectilePlugin;
+
+impl Plugin for EnemyProjectilePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<EnemyAttackTable>()
+            .init_asset_loader::<EnemyAttackTableLoader>()
+            .add_event::<EnemyShootEvent>()
+            .add_systems(OnExit(GameState::Loading), insert_enemy_attack_table)
+            .add_systems(
+                Update,
+                (
+                    hot_reload_enemy_attack_table,
+                    check_enemy_attack_archetypes.run_if(
+                        resource_changed::<EnemyAttackTable>()
+                            .or_else(resource_changed::<EnemyArchetypeTable>()),
+                    ),
+                    inject_enemy_ranged_attacks,
+                    tick_enemy_ranged_attacks,
+                    handle_enemy_shoot_event,
+                    handle_enemy_projectile_timers,
+                    handle_enemy_projectile_player_collisions,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GamePlay)),
+            );
+    }
+}
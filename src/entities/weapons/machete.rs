@@ -1,10 +1,15 @@
 use std::time::Duration;
 
-use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy::prelude::*;
+use bevy::render::{mesh::Indices, render_resource::PrimitiveTopology};
+use bevy::sprite::{MaterialMesh2dBundle, Mesh2dHandle};
 use bevy_particle_systems::Lerpable;
 
 use crate::GameState;
-use crate::{controls::ControlEvent, entities::player::Player};
+use crate::{controls::ControlEvent, entities::player::Player, sprites::CharacterAnimation};
+
+use super::weapon::{WeaponFireEvent, WeaponInventory, WeaponKind};
+use super::weapon_table::WeaponTable;
 
 // note to self: attack happens every 1.3 seconds but there is delay
 // for attack 0.3 secodns so i hinda hack this around
@@ -13,8 +18,31 @@ use crate::{controls::ControlEvent, entities::player::Player};
 // Components
 // ----------
 
+/// Shows where the machete hits and when.
 #[derive(Component, Clone, Copy, Default)]
-pub struct Machete {}
+pub struct Machete {
+    /// Arc the indicator mesh was built for.
+    pub arc: f32,
+}
+
+/// Short flash of the area hit by a swing.
+#[derive(Component, Clone, Default)]
+pub struct MacheteSlash {
+    pub timer: Timer,
+}
+
+// ------
+// Events
+// ------
+
+/// Sent for each machete swing, after the hits are dealt.
+#[derive(Event, Clone, Copy)]
+pub struct MacheteSlashEvent {
+    pub entity: Entity,
+    pub direction: Vec2,
+    pub area: f32,
+    pub arc: f32,
+}
 
 // -------
 // Bundles
@@ -24,15 +52,40 @@ pub struct Machete {}
 pub struct MacheteIndictorBundle {
     pub material_mesh_2d_bundle: MaterialMesh2dBundle<ColorMaterial>,
     pub machete_indicator: Machete,
-    pub timer_activation: MacheteTimer,
 }
 
-// ---------
-// Resources
-// ---------
+const SLASH_MILLIS: u64 = 150;
+
+/// Pie slice of radius 1 covering `arc` degrees around +X. The indicator and
+/// the slash are scaled and rotated from it.
+fn sector_mesh(arc: f32) -> Mesh {
+    let segments = (arc / 10.0).ceil().max(1.0) as u32;
+    let arc = arc.to_radians();
+
+    let mut positions = vec![[0.0, 0.0, 0.0]];
+    for i in 0..=segments {
+        let angle = -arc / 2.0 + arc * i as f32 / segments as f32;
+        positions.push([angle.cos(), angle.sin(), 0.0]);
+    }
+
+    let normals = vec![[0.0, 0.0, 1.0]; positions.len()];
+    let uvs: Vec<[f32; 2]> = positions
+        .iter()
+        .map(|[x, y, _]| [(x + 1.0) / 2.0, (1.0 - y) / 2.0])
+        .collect();
+    let indices = (1..=segments).flat_map(|i| [0, i, i + 1]).collect();
 
-#[derive(Resource, Default, Clone, Component)]
-pub struct MacheteTimer(pub Timer);
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U32(indices)));
+    mesh
+}
+
+fn facing_rotation(direction: Vec2) -> Quat {
+    Quat::from_rotation_z(direction.y.atan2(direction.x))
+}
 
 // -------
 // Systems
@@ -40,25 +93,24 @@ pub struct MacheteTimer(pub Timer);
 
 fn inject_machete_indicator(
     mut commands: Commands,
-    q_players: Query<(Entity, &Parent, &Transform, &Player)>,
+    q_players: Query<(Entity, &WeaponInventory), With<Player>>,
     mut q_machate_indicator: ParamSet<(Query<(&mut Transform, &Machete), Without<Player>>,)>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
 ) {
-    for (entity, _parent, _player_transform, _) in q_players.iter() {
-        if q_machate_indicator.p0().iter().count() == 0 {
-            let machete_timer = MacheteTimer(Timer::new(
-                Duration::from_secs_f32(1.0),
-                TimerMode::Repeating,
-            ));
+    for (entity, inventory) in q_players.iter() {
+        if inventory.get(WeaponKind::Machete).is_none() {
+            continue;
+        }
 
+        if q_machate_indicator.p0().iter().count() == 0 {
             commands.entity(entity).with_children(|parent| {
                 parent.spawn((
                     MacheteIndictorBundle {
-                        machete_indicator: Machete {},
-                        timer_activation: machete_timer.clone(),
+                        machete_indicator: Machete { arc: 360.0 },
                         material_mesh_2d_bundle: MaterialMesh2dBundle {
-                            mesh: meshes.add(shape::Circle::new(80.).into()).into(),
+                            // scaled to the hit radius in `animate_machete_indicator`
+                            mesh: meshes.add(sector_mesh(360.0)).into(),
                             material: materials.add(ColorMaterial::from(Color::PURPLE.with_a(0.5))),
                             ..default()
                         },
@@ -71,29 +123,55 @@ fn inject_machete_indicator(
     }
 }
 
+/// The swing itself is the player's attack animation, hits land in `event_player_attack`.
 pub fn handle_machete_attack(
-    time: Res<Time>,
-    mut q_machete: Query<(Entity, &Transform, &mut MacheteTimer), With<Machete>>,
+    mut ev_weapon_fire: EventReader<WeaponFireEvent>,
     mut ev_control: EventWriter<ControlEvent>,
 ) {
-    for (_, _, mut machete_timer) in q_machete.iter_mut() {
-        machete_timer.0.tick(time.delta());
-
-        if machete_timer.0.just_finished() {
-            ev_control.send(ControlEvent {
-                attack: true,
-                ..Default::default()
-            });
+    for ev in ev_weapon_fire.read() {
+        if ev.kind != WeaponKind::Machete {
+            continue;
         }
+
+        ev_control.send(ControlEvent {
+            attack: true,
+            ..Default::default()
+        });
     }
 }
 
 fn animate_machete_indicator(
-    mut q_machete: Query<(Entity, &mut Handle<ColorMaterial>, &mut MacheteTimer), With<Machete>>,
+    q_players: Query<(&WeaponInventory, &CharacterAnimation), With<Player>>,
+    mut q_machete: Query<(
+        &mut Machete,
+        &mut Transform,
+        &mut Mesh2dHandle,
+        &mut Handle<ColorMaterial>,
+    )>,
+    mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    weapon_table: Res<WeaponTable>,
 ) {
-    for (_, mut material, timer) in q_machete.iter_mut() {
-        let elapsed = timer.0.elapsed_secs();
+    let Some((machete, direction)) = q_players.iter().find_map(|(inventory, char_animation)| {
+        inventory
+            .get(WeaponKind::Machete)
+            .map(|machete| (machete, char_animation.direction.vec()))
+    }) else {
+        return;
+    };
+
+    let stats = machete.stats(&weapon_table);
+
+    for (mut indicator, mut transform, mut mesh, mut material) in q_machete.iter_mut() {
+        if indicator.arc != stats.arc {
+            indicator.arc = stats.arc;
+            *mesh = meshes.add(sector_mesh(stats.arc)).into();
+        }
+
+        transform.scale = Vec3::new(stats.area, stats.area, 1.0);
+        transform.rotation = facing_rotation(direction);
+
+        let elapsed = machete.cooldown.percent();
         let mut percentage = (1.0 - elapsed) / (1.0 - 0.3);
         if elapsed < 0.3 {
             percentage = 0.0;
@@ -103,6 +181,59 @@ fn animate_machete_indicator(
     }
 }
 
+fn event_machete_slash(
+    mut commands: Commands,
+    mut ev_machete_slash: EventReader<MacheteSlashEvent>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for ev in ev_machete_slash.read() {
+        let Some(mut player) = commands.get_entity(ev.entity) else {
+            continue;
+        };
+
+        player.with_children(|parent| {
+            parent.spawn((
+                MaterialMesh2dBundle {
+                    mesh: meshes.add(sector_mesh(ev.arc)).into(),
+                    material: materials.add(ColorMaterial::from(Color::WHITE.with_a(0.8))),
+                    transform: Transform {
+                        translation: Vec3::new(0.0, 0.0, 1.0),
+                        rotation: facing_rotation(ev.direction),
+                        scale: Vec3::new(ev.area, ev.area, 1.0),
+                    },
+                    ..default()
+                },
+                MacheteSlash {
+                    timer: Timer::new(Duration::from_millis(SLASH_MILLIS), TimerMode::Once),
+                },
+                Name::new("machete slash"),
+                ZIndex::Local(104),
+            ));
+        });
+    }
+}
+
+fn animate_machete_slash(
+    mut commands: Commands,
+    mut q_slashes: Query<(Entity, &mut MacheteSlash, &Handle<ColorMaterial>)>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    time: Res<Time>,
+) {
+    for (entity, mut slash, material) in q_slashes.iter_mut() {
+        slash.timer.tick(time.delta());
+
+        if slash.timer.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        if let Some(material) = materials.get_mut(material) {
+            material.color = Color::WHITE.with_a(0.8 * (1.0 - slash.timer.percent()));
+        }
+    }
+}
+
 // ------
 // Plugin
 // ------
@@ -114,7 +245,7 @@ pub struct MachetePlugin;
 
 impl Plugin for MachetePlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<MacheteTimer>()
+        app.add_event::<MacheteSlashEvent>()
             // Event Handlers
             .add_systems(
                 Update,
@@ -122,6 +253,8 @@ impl Plugin for MachetePlugin {
                     inject_machete_indicator,
                     handle_machete_attack,
                     animate_machete_indicator,
+                    event_machete_slash,
+                    animate_machete_slash,
                 )
                     .run_if(in_state(GameState::GamePlay)),
             );
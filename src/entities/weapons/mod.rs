@@ -1,7 +1,12 @@
+pub mod enemy_attack;
+pub mod enemy_projectile;
 pub mod machete;
+pub mod orbiting_pill;
 pub mod pill;
 pub mod speargun;
+pub mod weapon;
 pub mod weapon_arrow;
+pub mod weapon_table;
 
 use bevy::prelude::*;
 
@@ -10,10 +15,13 @@ pub struct WeaponsPlugin;
 impl Plugin for WeaponsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins((
-            // weapon_arrow::WeaponArrowPlugin,
+            weapon::WeaponPlugin,
+            weapon_arrow::WeaponArrowPlugin,
             speargun::WeaponSpeargunPlugin,
             pill::WeaponPillPlugin,
+            enemy_projectile::EnemyProjectilePlugin,
             machete::MachetePlugin,
+            orbiting_pill::WeaponOrbitingPillPlugin,
         ));
     }
 }
@@ -0,0 +1,159 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::entities::characters::enemy::{Enemy, EnemyHitEvent};
+use crate::entities::player::Player;
+use crate::{loading::StaticSpriteAssets, GameState};
+
+use super::weapon::{WeaponFireEvent, WeaponInventory, WeaponKind};
+use super::weapon_table::WeaponTable;
+
+/// Radians per second the pills turn around the player.
+const ORBIT_SPEED: f32 = 3.0;
+/// Enemies closer than this to a pill are hit by it.
+const PILL_HIT_RADIUS: f32 = 16.0;
+
+// ----------
+// Components
+// ----------
+
+/// Pill turning around the player, the same mechanic psychiatrists use.
+#[derive(Component, Clone, Copy, Default)]
+pub struct OrbitingPill {
+    /// Position of the pill in the ring.
+    pub index: u32,
+}
+
+// -------
+// Systems
+// -------
+
+/// Keeps as many pills around the player as the weapon level gives.
+fn inject_orbiting_pills(
+    mut commands: Commands,
+    q_players: Query<(Entity, &WeaponInventory), With<Player>>,
+    q_pills: Query<Entity, With<OrbitingPill>>,
+    weapon_table: Res<WeaponTable>,
+    static_sprite_assets: Res<StaticSpriteAssets>,
+) {
+    for (entity, inventory) in q_players.iter() {
+        let count = inventory
+            .get(WeaponKind::Pill)
+            .map(|pill| pill.stats(&weapon_table).projectiles)
+            .unwrap_or(0);
+
+        if q_pills.iter().count() == count as usize {
+            continue;
+        }
+
+        for pill in q_pills.iter() {
+            commands.entity(pill).despawn_recursive();
+        }
+
+        commands.entity(entity).with_children(|parent| {
+            for index in 0..count {
+                parent.spawn((
+                    SpriteBundle {
+                        texture: static_sprite_assets.pill.clone(),
+                        transform: Transform::from_scale(Vec3::ONE * 1.5),
+                        ..default()
+                    },
+                    OrbitingPill { index },
+                    Name::new("weapon orbiting pill"),
+                    ZIndex::Global(403),
+                ));
+            }
+        });
+    }
+}
+
+fn rotate_orbiting_pills(
+    q_players: Query<&WeaponInventory, With<Player>>,
+    mut q_pills: Query<(&OrbitingPill, &mut Transform)>,
+    weapon_table: Res<WeaponTable>,
+    time: Res<Time>,
+) {
+    let Some(stats) = q_players
+        .iter()
+        .find_map(|inventory| inventory.get(WeaponKind::Pill))
+        .map(|pill| pill.stats(&weapon_table))
+    else {
+        return;
+    };
+
+    let elapsed_seconds = time.elapsed_seconds() * ORBIT_SPEED;
+    let count = stats.projectiles.max(1) as f32;
+
+    for (pill, mut transform) in q_pills.iter_mut() {
+        let angle = elapsed_seconds + TAU * pill.index as f32 / count;
+
+        transform.translation = stats.area * Vec3::new(angle.cos(), angle.sin(), 0.0);
+        transform.rotation = -Quat::from_rotation_z(elapsed_seconds * 2.);
+    }
+}
+
+/// Each time the weapon fires, every enemy touching a pill is hit once.
+fn handle_orbiting_pill_attack(
+    mut ev_weapon_fire: EventReader<WeaponFireEvent>,
+    mut ev_enemy_hit: EventWriter<EnemyHitEvent>,
+    q_players: Query<&Transform, With<Player>>,
+    q_pills: Query<&Transform, (With<OrbitingPill>, Without<Player>)>,
+    q_enemies: Query<(Entity, &Transform, &Enemy), (Without<Player>, Without<OrbitingPill>)>,
+) {
+    for ev in ev_weapon_fire.read() {
+        if ev.kind != WeaponKind::Pill {
+            continue;
+        }
+
+        let Ok(player_transform) = q_players.get(ev.entity) else {
+            continue;
+        };
+
+        let pill_positions: Vec<Vec2> = q_pills
+            .iter()
+            .map(|transform| (player_transform.translation + transform.translation).truncate())
+            .collect();
+
+        for (entity, transform, enemy) in q_enemies.iter() {
+            if enemy.is_dummy || enemy.health == 0 {
+                continue;
+            }
+
+            let position = transform.translation.truncate();
+            if !pill_positions
+                .iter()
+                .any(|pill| pill.distance(position) < PILL_HIT_RADIUS)
+            {
+                continue;
+            }
+
+            ev_enemy_hit.send(EnemyHitEvent {
+                entity,
+                damage: ev.stats.damage,
+            });
+        }
+    }
+}
+
+// ------
+// Plugin
+// ------
+
+pub struct WeaponOrbitingPillPlugin;
+
+impl Plugin for WeaponOrbitingPillPlugin {
+    fn build(&self, app: &mut App) {
+        app
+            // Event Handlers
+            .add_systems(
+                Update,
+                (
+                    inject_orbiting_pills,
+                    rotate_orbiting_pills,
+                    handle_orbiting_pill_attack,
+                )
+                    .run_if(in_state(GameState::GamePlay)),
+            );
+    }
+}
@@ -1,9 +1,14 @@
 use std::f32::consts::FRAC_PI_2;
 use std::time::Duration;
 
+use crate::controls::ControlEvent;
 use crate::entities::characters::enemy::{Enemy, EnemyHitEvent};
 use crate::entities::player::Player;
+use crate::entities::text_indicator::SpawnTextIndicatorEvent;
+use crate::ldtk::WallCollider;
 use crate::physics::ColliderBundle;
+use crate::rng::{GameRng, GameRngSet};
+use crate::sprites::CharacterAnimation;
 use crate::{loading::StaticSpriteAssets, GameState};
 
 use bevy::prelude::*;
@@ -11,27 +16,73 @@ use bevy::window::PrimaryWindow;
 use bevy_magic_light_2d::SpriteCamera;
 use bevy_rapier2d::geometry::Collider;
 use bevy_rapier2d::prelude::*;
+use rand::seq::SliceRandom;
+
+use super::weapon::{WeaponFireEvent, WeaponInventory, WeaponKind};
+
+/// Enemies further than this from the player are never auto-aimed at.
+const TARGETING_RANGE: f32 = 400.0;
+
+// ----
+// Data
+// ----
+
+/// How the speargun picks where to shoot, switched with the aim mode control.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum SpeargunTargeting {
+    #[default]
+    Nearest,
+    Strongest,
+    Random,
+    /// Where the player walks, or faces when standing still.
+    Movement,
+    /// Aimed with the mouse or the arrow keys.
+    Manual,
+}
+
+impl SpeargunTargeting {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpeargunTargeting::Nearest => "NEAREST",
+            SpeargunTargeting::Strongest => "STRONGEST",
+            SpeargunTargeting::Random => "RANDOM",
+            SpeargunTargeting::Movement => "MOVEMENT",
+            SpeargunTargeting::Manual => "MANUAL",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            SpeargunTargeting::Nearest => SpeargunTargeting::Strongest,
+            SpeargunTargeting::Strongest => SpeargunTargeting::Random,
+            SpeargunTargeting::Random => SpeargunTargeting::Movement,
+            SpeargunTargeting::Movement => SpeargunTargeting::Manual,
+            SpeargunTargeting::Manual => SpeargunTargeting::Nearest,
+        }
+    }
+}
 
 // ----------
 // Components
 // ----------
 
 #[derive(Component, Clone, Copy, Default)]
-pub struct Speargun;
+pub struct Speargun {
+    pub targeting: SpeargunTargeting,
+    /// Enemy aimed at. Random targeting keeps it until the next shot.
+    pub target: Option<Entity>,
+}
 
 #[derive(Component, Clone, Copy, Default)]
-pub struct SpeargunArrow;
+pub struct SpeargunArrow {
+    pub damage: u16,
+    /// Enemies the arrow can still go through.
+    pub pierce: u32,
+}
 
 #[derive(Component, Clone, Copy, Default)]
 pub struct SpeargunArrowTrail;
 
-// ------
-// Events
-// ------
-
-#[derive(Event, Clone)]
-pub struct SpeargunShootEvent {}
-
 // -------
 // Bundles
 // -------
@@ -40,7 +91,6 @@ pub struct SpeargunShootEvent {}
 pub struct SpeargunBundle {
     pub sprite_bundle: SpriteBundle,
     pub speargun: Speargun,
-    pub timer_activation: SpeargunTimer,
 }
 
 #[derive(Clone, Default, Bundle)]
@@ -51,6 +101,8 @@ pub struct SpeargunArrowBundle {
     pub timer_trail_spawn: SpeargunTrailSpawnTimer,
     pub collider_bundle: ColliderBundle,
     pub active_events: ActiveEvents,
+    /// Arrows fly through enemies instead of pushing them, walls break them.
+    pub sensor: Sensor,
 }
 
 #[derive(Clone, Default, Bundle)]
@@ -64,9 +116,6 @@ pub struct SpeargunArrowTrailBundle {
 // Resources
 // ---------
 
-#[derive(Resource, Default, Clone, Component)]
-pub struct SpeargunTimer(pub Timer);
-
 #[derive(Resource, Default, Clone, Component)]
 pub struct SpeargunTrailSpawnTimer(pub Timer);
 
@@ -82,17 +131,16 @@ pub struct SpeargunArrowTrailDespawnTimer(pub Timer);
 
 fn inject_speargun_sprite(
     mut commands: Commands,
-    q_players: Query<(Entity, &Parent, &Transform, &Player)>,
+    q_players: Query<(Entity, &WeaponInventory), With<Player>>,
     mut q_spearguns: ParamSet<(Query<(&mut Transform, &Speargun), Without<Player>>,)>,
     static_sprite_assets: Res<StaticSpriteAssets>,
 ) {
-    for (entity, _parent, _player_transform, _) in q_players.iter() {
-        if q_spearguns.p0().iter().count() == 0 {
-            let timer_activation = SpeargunTimer(Timer::new(
-                Duration::from_secs_f32(1.0),
-                TimerMode::Repeating,
-            ));
+    for (entity, inventory) in q_players.iter() {
+        if inventory.get(WeaponKind::Speargun).is_none() {
+            continue;
+        }
 
+        if q_spearguns.p0().iter().count() == 0 {
             commands.entity(entity).with_children(|parent| {
                 parent.spawn((
                     SpeargunBundle {
@@ -102,8 +150,7 @@ fn inject_speargun_sprite(
                             // visibility: Visibility::Hidden,
                             ..default()
                         },
-                        speargun: Speargun,
-                        timer_activation: timer_activation.clone(),
+                        speargun: Speargun::default(),
                     },
                     Name::new("weapon speargun"),
                     ZIndex::Global(303),
@@ -113,91 +160,178 @@ fn inject_speargun_sprite(
     }
 }
 
+fn cycle_speargun_targeting(
+    mut ev_control: EventReader<ControlEvent>,
+    mut q_spearguns: Query<&mut Speargun>,
+    q_players: Query<Entity, With<Player>>,
+    mut ev_spawn_text_indicator: EventWriter<SpawnTextIndicatorEvent>,
+) {
+    let presses = ev_control.read().filter(|control| control.aim).count();
+    if presses == 0 {
+        return;
+    }
+
+    for mut speargun in q_spearguns.iter_mut() {
+        for _ in 0..presses {
+            speargun.targeting = speargun.targeting.next();
+        }
+        speargun.target = None;
+
+        for entity in q_players.iter() {
+            ev_spawn_text_indicator.send(SpawnTextIndicatorEvent {
+                text: format!("AIM: {}", speargun.targeting.name()),
+                entity,
+            });
+        }
+    }
+}
+
+fn aim_speargun(
+    mut q_spearguns: Query<(&mut Speargun, &mut Transform, &mut Sprite), Without<Player>>,
+    q_players: Query<(&Transform, &Velocity, &CharacterAnimation), With<Player>>,
+    q_enemies: Query<(Entity, &Transform, &Enemy), (Without<Player>, Without<Speargun>)>,
+    mut rng: ResMut<GameRng>,
+) {
+    let Ok((player_transform, velocity, char_animation)) = q_players.get_single() else {
+        return;
+    };
+
+    let player_position = player_transform.translation.truncate();
+
+    let in_range: Vec<(Entity, Vec2, u16)> = q_enemies
+        .iter()
+        .filter(|(_, _, enemy)| !enemy.is_dummy && enemy.health > 0)
+        .map(|(entity, transform, enemy)| (entity, transform.translation.truncate(), enemy.health))
+        .filter(|(_, position, _)| position.distance(player_position) < TARGETING_RANGE)
+        .collect();
+
+    for (mut speargun, mut transform, mut sprite) in q_spearguns.iter_mut() {
+        let direction = match speargun.targeting {
+            SpeargunTargeting::Manual => continue,
+            SpeargunTargeting::Movement => match velocity.linvel.length() > 1.0 {
+                true => Some(velocity.linvel),
+                false => Some(char_animation.direction.vec()),
+            },
+            targeting => {
+                let target = match targeting {
+                    SpeargunTargeting::Strongest => {
+                        in_range.iter().max_by_key(|(_, _, health)| *health)
+                    }
+                    SpeargunTargeting::Random => in_range
+                        .iter()
+                        .find(|(entity, _, _)| Some(*entity) == speargun.target)
+                        .or_else(|| in_range.choose(&mut *rng)),
+                    _ => in_range.iter().min_by(|(_, a, _), (_, b, _)| {
+                        a.distance(player_position)
+                            .total_cmp(&b.distance(player_position))
+                    }),
+                };
+
+                speargun.target = target.map(|(entity, _, _)| *entity);
+                target.map(|(_, position, _)| *position - player_position)
+            }
+        };
+
+        if let Some(direction) = direction {
+            point_speargun(&mut transform, &mut sprite, direction.y.atan2(direction.x));
+        }
+    }
+}
+
 const TRAIL_TIMER_SPAWN_MILLIS: u64 = 10;
+const ARROW_VELOCITY: f32 = 350.0;
+const ARROW_LIFETIME_SECS: f32 = 1.0;
+/// Angle between two arrows of the same shot.
+const ARROW_SPREAD: f32 = 0.2;
 
 fn handle_speargun_attack_event(
     mut commands: Commands,
     q_players: Query<(Entity, &Parent, &Transform, &Player)>,
-    q_spearguns: Query<(&mut Transform, &Speargun), Without<Player>>,
-    mut ev_arrow_attack: EventReader<SpeargunShootEvent>,
+    mut q_spearguns: Query<(&Transform, &mut Speargun), Without<Player>>,
+    mut ev_weapon_fire: EventReader<WeaponFireEvent>,
     static_sprite_assets: Res<StaticSpriteAssets>,
 ) {
-    for _ in ev_arrow_attack.read() {
-        for (speargun_transform, _) in q_spearguns.iter() {
+    for ev in ev_weapon_fire.read() {
+        if ev.kind != WeaponKind::Speargun {
+            continue;
+        }
+
+        let count = ev.stats.projectiles;
+
+        for (speargun_transform, mut speargun) in q_spearguns.iter_mut() {
+            if speargun.targeting == SpeargunTargeting::Random {
+                speargun.target = None;
+            }
+
             for (_, parent, player_transform, _) in q_players.iter() {
                 commands.entity(parent.get()).with_children(|parent| {
-                    let timer_despawn = SpeargunArrowDespawnTimer(Timer::new(
-                        Duration::from_secs_f32(1.0),
-                        TimerMode::Repeating,
-                    ));
-
-                    let timer_trail_spawn = SpeargunTrailSpawnTimer(Timer::new(
-                        Duration::from_millis(TRAIL_TIMER_SPAWN_MILLIS),
-                        TimerMode::Repeating,
-                    ));
-
-                    let z_rot = speargun_transform.rotation.to_euler(EulerRot::ZYX).0;
-                    let translation = player_transform.translation
-                        + 32.0 * Vec3::new(z_rot.cos(), z_rot.sin(), 0.0);
-                    let arrow_velocity = 350.0;
-
-                    parent.spawn((
-                        SpeargunArrowBundle {
-                            sprite_bundle: SpriteBundle {
-                                texture: static_sprite_assets.speargun_arrow.clone(),
-                                transform: Transform {
-                                    translation,
-                                    rotation: speargun_transform.rotation,
+                    let aim = speargun_transform.rotation.to_euler(EulerRot::ZYX).0;
+
+                    for i in 0..count {
+                        let timer_despawn = SpeargunArrowDespawnTimer(Timer::new(
+                            Duration::from_secs_f32(ARROW_LIFETIME_SECS),
+                            TimerMode::Repeating,
+                        ));
+
+                        let timer_trail_spawn = SpeargunTrailSpawnTimer(Timer::new(
+                            Duration::from_millis(TRAIL_TIMER_SPAWN_MILLIS),
+                            TimerMode::Repeating,
+                        ));
+
+                        let z_rot = aim + ARROW_SPREAD * (i as f32 - (count as f32 - 1.0) / 2.0);
+                        let translation = player_transform.translation
+                            + 32.0 * Vec3::new(z_rot.cos(), z_rot.sin(), 0.0);
+                        let arrow_velocity = ARROW_VELOCITY;
+
+                        parent.spawn((
+                            SpeargunArrowBundle {
+                                sprite_bundle: SpriteBundle {
+                                    texture: static_sprite_assets.speargun_arrow.clone(),
+                                    transform: Transform {
+                                        translation,
+                                        rotation: Quat::from_rotation_z(z_rot),
+                                        ..default()
+                                    },
                                     ..default()
                                 },
-                                ..default()
-                            },
-                            speargun_arrow: SpeargunArrow,
-                            active_events: ActiveEvents::COLLISION_EVENTS,
-                            timer_despawn,
-                            timer_trail_spawn,
-                            collider_bundle: ColliderBundle {
-                                collider: Collider::cuboid(20., 5.),
-                                rigid_body: RigidBody::Dynamic,
-                                friction: Friction {
-                                    coefficient: 0.0,
-                                    combine_rule: CoefficientCombineRule::Min,
+                                speargun_arrow: SpeargunArrow {
+                                    damage: ev.stats.damage,
+                                    pierce: ev.stats.pierce,
                                 },
-                                density: ColliderMassProperties::Density(105.0),
-                                rotation_constraints: LockedAxes::ROTATION_LOCKED_X,
-                                velocity: Velocity {
-                                    linvel: arrow_velocity
-                                        * Vec2 {
-                                            x: z_rot.cos(),
-                                            y: z_rot.sin(),
-                                        },
-                                    angvel: 0.0,
+                                active_events: ActiveEvents::COLLISION_EVENTS,
+                                sensor: Sensor,
+                                timer_despawn,
+                                timer_trail_spawn,
+                                collider_bundle: ColliderBundle {
+                                    collider: Collider::cuboid(20., 5.),
+                                    rigid_body: RigidBody::Dynamic,
+                                    friction: Friction {
+                                        coefficient: 0.0,
+                                        combine_rule: CoefficientCombineRule::Min,
+                                    },
+                                    density: ColliderMassProperties::Density(105.0),
+                                    rotation_constraints: LockedAxes::ROTATION_LOCKED_X,
+                                    velocity: Velocity {
+                                        linvel: arrow_velocity
+                                            * Vec2 {
+                                                x: z_rot.cos(),
+                                                y: z_rot.sin(),
+                                            },
+                                        angvel: 0.0,
+                                    },
+                                    ..default()
                                 },
-                                ..default()
                             },
-                        },
-                        Name::new("weapon speargun arrow"),
-                        ZIndex::Local(202),
-                    ));
+                            Name::new("weapon speargun arrow"),
+                            ZIndex::Local(202),
+                        ));
+                    }
                 });
             }
         }
     }
 }
 
-fn handle_speargun_attack(
-    mut q_speargun: Query<(Entity, &Speargun, &mut SpeargunTimer)>,
-    mut ev_arrow_attack: EventWriter<SpeargunShootEvent>,
-    time: Res<Time>,
-) {
-    for (_, _, mut timer) in q_speargun.iter_mut() {
-        timer.0.tick(time.delta());
-        if timer.0.just_finished() {
-            ev_arrow_attack.send(SpeargunShootEvent {});
-        }
-    }
-}
-
 const TRAIL_TIMER_DE_SPAWN_MILLIS: u64 = 500;
 
 fn handle_arrow_timers(
@@ -271,9 +405,11 @@ fn handle_trail_timers(
 // -------
 
 pub fn handle_arrow_enemy_collisions(
+    mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
     q_enemies: Query<(Entity, &Enemy)>,
-    q_arrows: Query<(Entity, &SpeargunArrow)>,
+    q_walls: Query<(), With<WallCollider>>,
+    mut q_arrows: Query<&mut SpeargunArrow>,
     mut ev_enemy_hit: EventWriter<EnemyHitEvent>,
 ) {
     for event in collision_events.read() {
@@ -287,6 +423,16 @@ pub fn handle_arrow_enemy_collisions(
             let contact_2_arrow = q_arrows.get(*e2);
             let is_arrow_contact = contact_1_arrow.is_ok() || contact_2_arrow.is_ok();
 
+            // arrows are sensors, walls have to break them here
+            if is_arrow_contact && (q_walls.contains(*e1) || q_walls.contains(*e2)) {
+                let arrow_entity = match contact_1_arrow.is_ok() {
+                    true => *e1,
+                    false => *e2,
+                };
+                commands.entity(arrow_entity).despawn_recursive();
+                continue;
+            }
+
             if !(is_enemy_contact && is_arrow_contact) {
                 continue;
             }
@@ -296,10 +442,24 @@ pub fn handle_arrow_enemy_collisions(
                 false => contact_2_enemy.unwrap().0,
             };
 
+            let arrow_entity = match contact_1_arrow.is_ok() {
+                true => *e1,
+                false => *e2,
+            };
+
+            let Ok(mut arrow) = q_arrows.get_mut(arrow_entity) else {
+                continue;
+            };
+
             ev_enemy_hit.send(EnemyHitEvent {
                 entity: enemy_entity,
-                damage: 50,
+                damage: arrow.damage,
             });
+
+            match arrow.pierce {
+                0 => commands.entity(arrow_entity).despawn_recursive(),
+                _ => arrow.pierce -= 1,
+            }
         }
     }
 }
@@ -308,14 +468,22 @@ pub fn handle_arrow_enemy_collisions(
 // Controls
 // --------
 
+fn point_speargun(transform: &mut Transform, sprite: &mut Sprite, angle: f32) {
+    transform.rotation = Quat::from_rotation_z(angle);
+
+    sprite.flip_y = angle.abs() >= FRAC_PI_2;
+}
+
 fn rotate_speargun(
     mut q_speargun: Query<(&mut Transform, &mut Sprite, &Speargun), Without<Player>>,
     angle: f32,
 ) {
-    for (mut transform, mut sprite, _) in q_speargun.iter_mut() {
-        transform.rotation = Quat::from_rotation_z(angle);
+    for (mut transform, mut sprite, speargun) in q_speargun.iter_mut() {
+        if speargun.targeting != SpeargunTargeting::Manual {
+            continue;
+        }
 
-        sprite.flip_y = angle.abs() >= FRAC_PI_2;
+        point_speargun(&mut transform, &mut sprite, angle);
     }
 }
 
@@ -359,8 +527,11 @@ fn control_speargun_with_mouse(
     q_players: Query<(Entity, &GlobalTransform, &Player)>,
     q_speargun: Query<(&mut Transform, &mut Sprite, &Speargun), Without<Player>>,
 ) {
-    let (camera, camera_transform) = q_camera.single();
-    let window = q_window.single();
+    let (Ok((camera, camera_transform)), Ok(window)) =
+        (q_camera.get_single(), q_window.get_single())
+    else {
+        return;
+    };
 
     let mut cursor_position = Vec2::ZERO;
     if let Some(world_position) = window
@@ -394,14 +565,18 @@ pub struct WeaponSpeargunPlugin;
 
 impl Plugin for WeaponSpeargunPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<SpeargunTimer>()
+        app
             // Event Handlers
             .add_systems(
                 Update,
                 (
                     inject_speargun_sprite,
-                    handle_speargun_attack,
-                    handle_speargun_attack_event,
+                    (
+                        cycle_speargun_targeting,
+                        aim_speargun.in_set(GameRngSet::AimSpeargun),
+                        handle_speargun_attack_event,
+                    )
+                        .chain(),
                     handle_arrow_timers,
                     handle_trail_timers,
                     handle_arrow_enemy_collisions,
@@ -409,7 +584,6 @@ impl Plugin for WeaponSpeargunPlugin {
                     control_speargun_with_mouse,
                 )
                     .run_if(in_state(GameState::GamePlay)),
-            )
-            .add_event::<SpeargunShootEvent>();
+            );
     }
 }
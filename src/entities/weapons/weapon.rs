@@ -0,0 +1,275 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    entities::player::Player,
+    gameplay::experience::Perks,
+    ui::{UIWeaponLoadout, UIWeaponName},
+    GameState,
+};
+
+use super::weapon_table::{
+    hot_reload_weapon_table, insert_weapon_table, WeaponStats, WeaponTable, WeaponTableLoader,
+};
+
+// ----
+// Data
+// ----
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Reflect, Serialize, Deserialize)]
+pub enum WeaponKind {
+    Machete,
+    Speargun,
+    Bow,
+    /// Pills orbiting the player.
+    Pill,
+}
+
+impl WeaponKind {
+    pub const ALL: [WeaponKind; 4] = [
+        WeaponKind::Machete,
+        WeaponKind::Speargun,
+        WeaponKind::Bow,
+        WeaponKind::Pill,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WeaponKind::Machete => "MACHETE",
+            WeaponKind::Speargun => "SPEARGUN",
+            WeaponKind::Bow => "BOW",
+            WeaponKind::Pill => "PILLS",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            WeaponKind::Machete => "sprites/machete.png",
+            WeaponKind::Speargun => "sprites/speargun.png",
+            WeaponKind::Bow => "sprites/arrow.png",
+            WeaponKind::Pill => "sprites/pill.png",
+        }
+    }
+}
+
+/// A weapon in the player's inventory.
+#[derive(Clone, Debug, Reflect)]
+pub struct Weapon {
+    pub kind: WeaponKind,
+    pub level: u32,
+    /// Its duration follows the weapon table, it is set in `tick_weapons`.
+    pub cooldown: Timer,
+}
+
+impl Weapon {
+    pub fn new(kind: WeaponKind, level: u32) -> Self {
+        Weapon {
+            kind,
+            level,
+            cooldown: Timer::new(Duration::ZERO, TimerMode::Repeating),
+        }
+    }
+
+    pub fn stats(&self, weapon_table: &WeaponTable) -> WeaponStats {
+        weapon_table.stats(self.kind, self.level)
+    }
+}
+
+// ----------
+// Components
+// ----------
+
+/// Weapons carried by the player, each one attacks on its own cooldown.
+#[derive(Component, Clone, Debug, Default, Reflect)]
+pub struct WeaponInventory {
+    pub weapons: Vec<Weapon>,
+}
+
+impl WeaponInventory {
+    pub fn starting() -> Self {
+        WeaponInventory {
+            weapons: vec![
+                Weapon::new(WeaponKind::Machete, 1),
+                Weapon::new(WeaponKind::Speargun, 1),
+            ],
+        }
+    }
+
+    pub fn get(&self, kind: WeaponKind) -> Option<&Weapon> {
+        self.weapons.iter().find(|weapon| weapon.kind == kind)
+    }
+
+    pub fn level(&self, kind: WeaponKind) -> u32 {
+        self.get(kind).map(|weapon| weapon.level).unwrap_or(0)
+    }
+
+    pub fn can_acquire(&self, kind: WeaponKind, weapon_table: &WeaponTable) -> bool {
+        self.level(kind) < weapon_table.max_level(kind)
+    }
+
+    /// Adds `kind` at level 1, or levels it up if it is already carried.
+    pub fn acquire(&mut self, kind: WeaponKind, weapon_table: &WeaponTable) {
+        match self.weapons.iter_mut().find(|weapon| weapon.kind == kind) {
+            Some(weapon) => {
+                weapon.level = (weapon.level + 1).min(weapon_table.max_level(kind));
+            }
+            None => self.weapons.push(Weapon::new(kind, 1)),
+        }
+    }
+}
+
+// ------
+// Events
+// ------
+
+/// Gives the player a weapon, or a level of one they already carry.
+#[derive(Event, Clone, Copy)]
+pub struct AcquireWeaponEvent {
+    pub kind: WeaponKind,
+}
+
+/// Sent when a weapon's cooldown runs out, each weapon module attacks on the
+/// ones of its kind.
+#[derive(Event, Clone, Copy)]
+pub struct WeaponFireEvent {
+    pub entity: Entity,
+    pub kind: WeaponKind,
+    pub stats: WeaponStats,
+}
+
+// --------------
+// Event Handlers
+// --------------
+
+pub fn event_acquire_weapon(
+    mut ev_acquire_weapon: EventReader<AcquireWeaponEvent>,
+    mut q_inventory: Query<&mut WeaponInventory, With<Player>>,
+    weapon_table: Res<WeaponTable>,
+) {
+    for ev in ev_acquire_weapon.read() {
+        for mut inventory in q_inventory.iter_mut() {
+            inventory.acquire(ev.kind, &weapon_table);
+        }
+    }
+}
+
+// -------
+// Systems
+// -------
+
+pub fn tick_weapons(
+    time: Res<Time>,
+    perks: Res<Perks>,
+    weapon_table: Res<WeaponTable>,
+    mut q_inventory: Query<(Entity, &mut WeaponInventory), With<Player>>,
+    mut ev_weapon_fire: EventWriter<WeaponFireEvent>,
+) {
+    let delta = time.delta().mul_f32(perks.attack_speed_multiplier());
+
+    for (entity, mut inventory) in q_inventory.iter_mut() {
+        for weapon in inventory.weapons.iter_mut() {
+            // level-ups and table reloads change the cooldown
+            let stats = weapon.stats(&weapon_table);
+            if weapon.cooldown.duration() != stats.cooldown {
+                weapon.cooldown.set_duration(stats.cooldown);
+            }
+
+            weapon.cooldown.tick(delta);
+
+            if weapon.cooldown.just_finished() {
+                ev_weapon_fire.send(WeaponFireEvent {
+                    entity,
+                    kind: weapon.kind,
+                    stats,
+                });
+            }
+        }
+    }
+}
+
+/// Redraws the weapon list in the corner when a weapon is acquired or leveled up.
+pub fn ui_weapon_loadout(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut drawn_loadout: Local<Vec<(WeaponKind, u32)>>,
+    q_inventory: Query<&WeaponInventory, With<Player>>,
+    q_loadout: Query<(Entity, Option<&Children>), With<UIWeaponLoadout>>,
+) {
+    let Ok(inventory) = q_inventory.get_single() else {
+        return;
+    };
+
+    let loadout: Vec<_> = inventory
+        .weapons
+        .iter()
+        .map(|weapon| (weapon.kind, weapon.level))
+        .collect();
+
+    for (entity, children) in q_loadout.iter() {
+        if children.is_some() && loadout == *drawn_loadout {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|parent| {
+                for (kind, level) in loadout.iter() {
+                    parent.spawn((
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Px(160.0),
+                                height: Val::Px(22.5),
+                                ..default()
+                            },
+                            background_color: Color::WHITE.into(),
+                            ..default()
+                        },
+                        UiImage::new(asset_server.load(kind.icon())),
+                    ));
+                    parent.spawn((
+                        TextBundle::from_section(
+                            format!("{} LV {}", kind.name(), level),
+                            TextStyle {
+                                font: asset_server.load("fonts/PixeloidMono-d94EV.ttf"),
+                                font_size: 20.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        UIWeaponName,
+                    ));
+                }
+            });
+    }
+
+    *drawn_loadout = loadout;
+}
+
+// ------
+// Plugin
+// ------
+
+pub struct WeaponPlugin;
+
+impl Plugin for WeaponPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<WeaponTable>()
+            .init_asset_loader::<WeaponTableLoader>()
+            .add_event::<AcquireWeaponEvent>()
+            .add_event::<WeaponFireEvent>()
+            .add_systems(OnExit(GameState::Loading), insert_weapon_table)
+            .add_systems(
+                Update,
+                (
+                    hot_reload_weapon_table,
+                    event_acquire_weapon,
+                    tick_weapons,
+                    ui_weapon_loadout,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GamePlay)),
+            );
+    }
+}
@@ -1,31 +1,31 @@
+use std::f32::consts::PI;
 use std::time::Duration;
 
 use bevy::prelude::*;
-use bevy_tweening::{lens::TransformPositionLens, Animator, EaseFunction, Tween};
+use bevy_rapier2d::prelude::*;
 
-use crate::entities::{
-    characters::enemy::{Enemy, EnemyHitEvent},
-    player::Player,
-};
+use crate::entities::characters::enemy::{Enemy, EnemyHitEvent};
+use crate::entities::player::Player;
+use crate::ldtk::WallCollider;
+use crate::physics::ColliderBundle;
 use crate::{loading::StaticSpriteAssets, GameState};
 
+use super::weapon::{WeaponFireEvent, WeaponKind};
+
 // ----------
 // Components
 // ----------
 
+/// Arrow shot by the bow.
 #[derive(Component, Clone, Copy, Default)]
-pub enum WeaponArrow {
-    #[default]
-    Right,
-    Left,
+pub struct WeaponArrow {
+    pub damage: u16,
+    /// Enemies the arrow can still go through.
+    pub pierce: u32,
 }
 
-// ------
-// Events
-// ------
-
-#[derive(Event, Clone)]
-pub struct WeaponArrowAttackEvent {}
+#[derive(Component, Clone, Copy, Default)]
+pub struct WeaponArrowTrail;
 
 // -------
 // Bundles
@@ -35,8 +35,19 @@ pub struct WeaponArrowAttackEvent {}
 pub struct WeaponArrowBundle {
     pub sprite_bundle: SpriteBundle,
     pub weapon_arrow: WeaponArrow,
-    pub timer_activation: WeaponArrowTimer,
-    pub timer_deactivation: WeaponArrowHideTimer,
+    pub timer_despawn: WeaponArrowDespawnTimer,
+    pub timer_trail_spawn: WeaponArrowTrailSpawnTimer,
+    pub collider_bundle: ColliderBundle,
+    pub active_events: ActiveEvents,
+    /// Arrows fly through enemies instead of pushing them, walls break them.
+    pub sensor: Sensor,
+}
+
+#[derive(Clone, Default, Bundle)]
+pub struct WeaponArrowTrailBundle {
+    pub sprite_bundle: SpriteBundle,
+    pub weapon_arrow_trail: WeaponArrowTrail,
+    pub timer_despawn: WeaponArrowTrailDespawnTimer,
 }
 
 // ---------
@@ -44,170 +55,210 @@ pub struct WeaponArrowBundle {
 // ---------
 
 #[derive(Resource, Default, Clone, Component)]
-pub struct WeaponArrowTimer(pub Timer);
+pub struct WeaponArrowDespawnTimer(pub Timer);
+
+#[derive(Resource, Default, Clone, Component)]
+pub struct WeaponArrowTrailSpawnTimer(pub Timer);
 
 #[derive(Resource, Default, Clone, Component)]
-pub struct WeaponArrowHideTimer(pub Timer);
+pub struct WeaponArrowTrailDespawnTimer(pub Timer);
+
+// -------
+// Systems
+// -------
 
-fn inject_arrow_sprite(
+const ARROW_VELOCITY: f32 = 400.0;
+const ARROW_LIFETIME_SECS: f32 = 0.8;
+/// Angle between two arrows shot to the same side.
+const ARROW_SPREAD: f32 = 0.25;
+const TRAIL_TIMER_SPAWN_MILLIS: u64 = 20;
+const TRAIL_TIMER_DE_SPAWN_MILLIS: u64 = 300;
+
+/// The bow shoots to both sides of the player, alternating right and left.
+fn handle_bow_attack_event(
     mut commands: Commands,
-    q_players: Query<(Entity, &Parent, &Transform, &Player)>,
-    mut q_arrows: ParamSet<(Query<(&mut Transform, &WeaponArrow), Without<Player>>,)>,
+    q_players: Query<(&Parent, &Transform), With<Player>>,
+    mut ev_weapon_fire: EventReader<WeaponFireEvent>,
     static_sprite_assets: Res<StaticSpriteAssets>,
 ) {
-    for (entity, _parent, _player_transform, _) in q_players.iter() {
-        if q_arrows.p0().iter().count() == 0 {
-            let timer_activation = WeaponArrowTimer(Timer::new(
-                Duration::from_secs_f32(1.0),
-                TimerMode::Repeating,
-            ));
+    for ev in ev_weapon_fire.read() {
+        if ev.kind != WeaponKind::Bow {
+            continue;
+        }
 
-            let mut timer_hide = WeaponArrowHideTimer(Timer::new(
-                Duration::from_secs_f32(0.2),
-                TimerMode::Repeating,
-            ));
+        let Ok((parent, player_transform)) = q_players.get(ev.entity) else {
+            continue;
+        };
 
-            timer_hide.0.pause();
+        let count = ev.stats.projectiles;
+
+        commands.entity(parent.get()).with_children(|parent| {
+            for i in 0..count {
+                let (base, side_count) = match i % 2 {
+                    0 => (0.0, (count + 1) / 2),
+                    _ => (PI, count / 2),
+                };
+                let z_rot =
+                    base + ARROW_SPREAD * ((i / 2) as f32 - (side_count as f32 - 1.0) / 2.0);
+                let direction = Vec2::new(z_rot.cos(), z_rot.sin());
 
-            commands.entity(entity).with_children(|parent| {
                 parent.spawn((
                     WeaponArrowBundle {
                         sprite_bundle: SpriteBundle {
                             texture: static_sprite_assets.arrow.clone(),
-                            transform: Transform::from_translation(Vec3::new(20.0, 0., 0.)),
-                            visibility: Visibility::Hidden,
+                            transform: Transform {
+                                translation: player_transform.translation
+                                    + 20.0 * direction.extend(0.0),
+                                rotation: Quat::from_rotation_z(z_rot),
+                                ..default()
+                            },
+                            ..default()
+                        },
+                        weapon_arrow: WeaponArrow {
+                            damage: ev.stats.damage,
+                            pierce: ev.stats.pierce,
+                        },
+                        timer_despawn: WeaponArrowDespawnTimer(Timer::new(
+                            Duration::from_secs_f32(ARROW_LIFETIME_SECS),
+                            TimerMode::Once,
+                        )),
+                        timer_trail_spawn: WeaponArrowTrailSpawnTimer(Timer::new(
+                            Duration::from_millis(TRAIL_TIMER_SPAWN_MILLIS),
+                            TimerMode::Repeating,
+                        )),
+                        collider_bundle: ColliderBundle {
+                            collider: Collider::cuboid(10., 3.),
+                            rigid_body: RigidBody::Dynamic,
+                            rotation_constraints: LockedAxes::ROTATION_LOCKED,
+                            velocity: Velocity {
+                                linvel: ARROW_VELOCITY * direction,
+                                angvel: 0.0,
+                            },
                             ..default()
                         },
-                        weapon_arrow: WeaponArrow::Right,
-                        timer_activation: timer_activation.clone(),
-                        timer_deactivation: timer_hide.clone(),
+                        active_events: ActiveEvents::COLLISION_EVENTS,
+                        sensor: Sensor,
                     },
                     Name::new("weapon arrow"),
-                    ZIndex::Local(103),
+                    ZIndex::Local(202),
                 ));
-            });
+            }
+        });
+    }
+}
+
+fn handle_arrow_timers(
+    mut commands: Commands,
+    mut q_arrows: Query<(
+        Entity,
+        &Parent,
+        &Transform,
+        &mut WeaponArrowDespawnTimer,
+        &mut WeaponArrowTrailSpawnTimer,
+    )>,
+    static_sprite_assets: Res<StaticSpriteAssets>,
+    time: Res<Time>,
+) {
+    for (entity, parent, transform, mut timer_despawn, mut timer_trail) in q_arrows.iter_mut() {
+        timer_despawn.0.tick(time.delta());
+        timer_trail.0.tick(time.delta());
+
+        if timer_despawn.0.just_finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
 
-            commands.entity(entity).with_children(|parent| {
+        if timer_trail.0.just_finished() {
+            commands.entity(parent.get()).with_children(|parent| {
                 parent.spawn((
-                    WeaponArrowBundle {
+                    WeaponArrowTrailBundle {
                         sprite_bundle: SpriteBundle {
                             sprite: Sprite {
-                                flip_x: true,
+                                color: Color::rgba(1.0, 1.0, 1.0, 0.4),
                                 ..default()
                             },
                             texture: static_sprite_assets.arrow.clone(),
-                            transform: Transform::from_translation(Vec3::new(-20.0, 0., 0.)),
-                            visibility: Visibility::Hidden,
+                            transform: *transform,
                             ..default()
                         },
-                        weapon_arrow: WeaponArrow::Left,
-                        timer_activation: timer_activation.clone(),
-                        timer_deactivation: timer_hide.clone(),
+                        weapon_arrow_trail: WeaponArrowTrail,
+                        timer_despawn: WeaponArrowTrailDespawnTimer(Timer::new(
+                            Duration::from_millis(TRAIL_TIMER_DE_SPAWN_MILLIS),
+                            TimerMode::Once,
+                        )),
                     },
-                    Name::new("weapon arrow"),
-                    ZIndex::Local(103),
+                    ZIndex::Local(105),
+                    Name::new("weapon arrow trail"),
                 ));
             });
         }
     }
 }
 
-fn animate_arrow(
+fn handle_trail_timers(
     mut commands: Commands,
-    mut queries: ParamSet<(
-        Query<(&Parent, &Transform, &Player)>,
-        Query<(
-            Entity,
-            &mut Transform,
-            &mut Visibility,
-            &WeaponArrow,
-            &mut WeaponArrowTimer,
-            &mut WeaponArrowHideTimer,
-        )>,
-    )>,
-    mut ev_arrow_attack: EventWriter<WeaponArrowAttackEvent>,
+    mut q_arrow_trails: Query<(Entity, &mut Sprite, &mut WeaponArrowTrailDespawnTimer)>,
     time: Res<Time>,
 ) {
-    if queries.p0().iter().next().is_none() {
-        return;
-    }
-
-    for (entity, mut transform, mut visibility, arrow, mut timer_activate, mut timer_hide) in
-        queries.p1().iter_mut()
-    {
-        timer_activate.0.tick(time.delta());
-        timer_hide.0.tick(time.delta());
-
-        if timer_activate.0.just_finished() {
-            *visibility = Visibility::Visible;
-
-            timer_hide.0.unpause();
-
-            let end = match arrow {
-                WeaponArrow::Right => Vec3::new(55., 0., 0.),
-                WeaponArrow::Left => Vec3::new(-55., 0., 0.),
-            };
-
-            let tween = Tween::new(
-                EaseFunction::QuadraticInOut,
-                Duration::from_secs_f32(0.15),
-                TransformPositionLens {
-                    start: match arrow {
-                        WeaponArrow::Right => Vec3::new(20., 0., 0.),
-                        WeaponArrow::Left => Vec3::new(-20., 0., 0.),
-                    },
-                    end,
-                },
-            );
-
-            ev_arrow_attack.send(WeaponArrowAttackEvent {});
-            commands.entity(entity).insert(Animator::new(tween));
+    for (entity, mut sprite, mut timer) in q_arrow_trails.iter_mut() {
+        timer.0.tick(time.delta());
+        if timer.0.just_finished() {
+            commands.entity(entity).despawn();
         }
 
-        if timer_hide.0.just_finished() {
-            timer_hide.0.pause();
-            *visibility = Visibility::Hidden;
-
-            transform.translation = match arrow {
-                WeaponArrow::Right => Vec3::new(20., 0., 0.),
-                WeaponArrow::Left => Vec3::new(-20., 0., 0.),
-            };
-        }
+        let opacity = (1.0 - timer.0.percent()) * 0.4;
+        sprite.color = Color::rgba(1.0, 1.0, 1.0, opacity);
     }
 }
 
-fn handle_arrow_attack(
-    mut arrow_attack_events: EventReader<WeaponArrowAttackEvent>,
+// -------
+// Physics
+// -------
+
+pub fn handle_arrow_enemy_collisions(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    q_enemies: Query<Entity, With<Enemy>>,
+    q_walls: Query<(), With<WallCollider>>,
+    mut q_arrows: Query<&mut WeaponArrow>,
     mut ev_enemy_hit: EventWriter<EnemyHitEvent>,
-    mut queries: ParamSet<(
-        Query<(&Transform, &Player)>,
-        Query<(Entity, &Transform, &Enemy)>,
-    )>,
 ) {
-    for _ in arrow_attack_events.read() {
-        if queries.p0().iter().len() == 0 {
-            return;
-        }
-
-        let player_translation = queries.p0().iter().next().unwrap().0.translation;
-
-        for (e, transfrom, _) in queries.p1().iter() {
-            let translation = transfrom.translation;
-
-            if (translation.z - player_translation.z).abs() > 16.0 {
+    for event in collision_events.read() {
+        let CollisionEvent::Started(e1, e2, _) = event else {
+            continue;
+        };
+
+        // arrows are sensors, walls have to break them here
+        match (q_arrows.contains(*e1), q_arrows.contains(*e2)) {
+            (true, _) if q_walls.contains(*e2) => {
+                commands.entity(*e1).despawn_recursive();
                 continue;
             }
-
-            let distance = translation.distance(player_translation).abs();
-
-            if distance > 40.0 {
+            (_, true) if q_walls.contains(*e1) => {
+                commands.entity(*e2).despawn_recursive();
                 continue;
             }
+            _ => {}
+        }
 
-            ev_enemy_hit.send(EnemyHitEvent {
-                entity: e,
-                damage: 88,
-            });
+        let (arrow_entity, enemy_entity) = match (q_arrows.contains(*e1), q_enemies.contains(*e2)) {
+            (true, true) => (*e1, *e2),
+            _ if q_arrows.contains(*e2) && q_enemies.contains(*e1) => (*e2, *e1),
+            _ => continue,
+        };
+
+        let Ok(mut arrow) = q_arrows.get_mut(arrow_entity) else {
+            continue;
+        };
+
+        ev_enemy_hit.send(EnemyHitEvent {
+            entity: enemy_entity,
+            damage: arrow.damage,
+        });
+
+        match arrow.pierce {
+            0 => commands.entity(arrow_entity).despawn_recursive(),
+            _ => arrow.pierce -= 1,
         }
     }
 }
@@ -220,13 +271,17 @@ pub struct WeaponArrowPlugin;
 
 impl Plugin for WeaponArrowPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<WeaponArrowTimer>()
+        app
             // Event Handlers
             .add_systems(
                 Update,
-                (inject_arrow_sprite, animate_arrow, handle_arrow_attack)
+                (
+                    handle_bow_attack_event,
+                    handle_arrow_timers,
+                    handle_trail_timers,
+                    handle_arrow_enemy_collisions,
+                )
                     .run_if(in_state(GameState::GamePlay)),
-            )
-            .add_event::<WeaponArrowAttackEvent>();
+            );
     }
 }
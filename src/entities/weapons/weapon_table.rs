@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::{BoxedFuture, HashMap};
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::gameplay::level_waves::deserialize_secs;
+use crate::loading::WeaponAssets;
+
+use super::weapon::WeaponKind;
+
+// -----
+// Asset
+// -----
+
+/// Stats of a weapon at one of its levels.
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct WeaponStats {
+    /// Time between two attacks, before the attack speed perk.
+    #[serde(deserialize_with = "deserialize_secs")]
+    pub cooldown: Duration,
+    pub damage: u16,
+    /// Hit radius of melee attacks, orbit radius of orbiting weapons.
+    #[serde(default)]
+    pub area: f32,
+    /// Angle in degrees covered by melee attacks, centered on the direction
+    /// the player faces. 360 hits all around.
+    #[serde(default = "full_circle")]
+    pub arc: f32,
+    /// Projectiles fired on each attack, or objects kept in orbit.
+    #[serde(default)]
+    pub projectiles: u32,
+    /// Enemies a projectile goes through before it breaks.
+    #[serde(default)]
+    pub pierce: u32,
+}
+
+fn full_circle() -> f32 {
+    360.0
+}
+
+/// Level tables of all weapons, loaded from `assets/weapons/player.weapons.ron`.
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, Debug)]
+pub struct WeaponTable {
+    /// Stats of each weapon, the first entry is level 1.
+    pub levels: HashMap<WeaponKind, Vec<WeaponStats>>,
+}
+
+impl WeaponTable {
+    /// Highest level `kind` can be upgraded to.
+    pub fn max_level(&self, kind: WeaponKind) -> u32 {
+        self.levels.get(&kind).map(Vec::len).unwrap_or(0) as u32
+    }
+
+    /// Stats of `kind` at `level`, levels past the end of the table use the last entry.
+    pub fn stats(&self, kind: WeaponKind, level: u32) -> WeaponStats {
+        let levels = &self.levels[&kind];
+        let index = (level.max(1) as usize - 1).min(levels.len() - 1);
+
+        levels[index]
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for kind in WeaponKind::ALL {
+            let Some(levels) = self.levels.get(&kind) else {
+                return Err(format!("{} has no level table", kind.name()));
+            };
+
+            if levels.is_empty() {
+                return Err(format!("{} has no levels", kind.name()));
+            }
+
+            for (i, stats) in levels.iter().enumerate() {
+                if stats.cooldown.is_zero() {
+                    return Err(format!("{} level {} has zero cooldown", kind.name(), i + 1));
+                }
+                if !stats.area.is_finite() || stats.area < 0.0 {
+                    return Err(format!(
+                        "{} level {} has a negative area",
+                        kind.name(),
+                        i + 1
+                    ));
+                }
+                if !(stats.arc > 0.0 && stats.arc <= 360.0) {
+                    return Err(format!(
+                        "{} level {} has an arc outside of 0-360 degrees",
+                        kind.name(),
+                        i + 1
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// ------
+// Loader
+// ------
+
+#[derive(Debug, Error)]
+pub enum WeaponTableLoaderError {
+    #[error("could not read weapon table: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse weapon table: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid weapon table: {0}")]
+    Invalid(String),
+}
+
+#[derive(Default)]
+pub struct WeaponTableLoader;
+
+impl AssetLoader for WeaponTableLoader {
+    type Asset = WeaponTable;
+    type Settings = ();
+    type Error = WeaponTableLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let weapon_table = ron::de::from_bytes::<WeaponTable>(&bytes)?;
+            weapon_table
+                .validate()
+                .map_err(WeaponTableLoaderError::Invalid)?;
+
+            Ok(weapon_table)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["weapons.ron"]
+    }
+}
+
+// -------
+// Systems
+// -------
+
+pub fn insert_weapon_table(
+    mut commands: Commands,
+    weapon_assets: Res<WeaponAssets>,
+    weapon_tables: Res<Assets<WeaponTable>>,
+) {
+    let weapon_table = weapon_tables
+        .get(&weapon_assets.table)
+        .expect("weapon table is loaded with the other assets");
+
+    commands.insert_resource(weapon_table.clone());
+}
+
+/// Picks up edits of the weapon table while the game runs, carried weapons
+/// switch to the new stats right away.
+pub fn hot_reload_weapon_table(
+    mut er_weapon_table: EventReader<AssetEvent<WeaponTable>>,
+    weapon_tables: Res<Assets<WeaponTable>>,
+    mut weapon_table: ResMut<WeaponTable>,
+) {
+    for event in er_weapon_table.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+
+        let Some(reloaded) = weapon_tables.get(*id) else {
+            continue;
+        };
+
+        *weapon_table = reloaded.clone();
+        info!("weapon table reloaded");
+    }
+}
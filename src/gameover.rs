@@ -1,14 +1,40 @@
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 
-use crate::{ui::UIGameOver, AudioAssets, ButtonColors, ChangeState, FontAssets, GameState};
+use crate::{
+    gameplay::{
+        scoring::{RunStats, Score},
+        waves::GameplayState,
+    },
+    rng::GameRng,
+    ui::UIGameOver,
+    AudioAssets, ButtonColors, ChangeState, FontAssets, GameState,
+};
 
+/// Sent when the player's health reaches zero.
 #[derive(Event, Clone)]
 pub struct GameOverEvent;
 
+/// Sent when the last wave is cleared.
 #[derive(Event, Clone)]
 pub struct GameWinEvent;
 
+/// How the last run ended, set when leaving gameplay for the game over screen.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RunOutcome {
+    Died,
+    Won,
+}
+
+impl RunOutcome {
+    fn title(&self) -> &'static str {
+        match self {
+            RunOutcome::Died => "  JUEGO\nTERMINADO",
+            RunOutcome::Won => "  JUEGO\n GANADO",
+        }
+    }
+}
+
 #[derive(Component)]
 struct UIGameOverButton;
 
@@ -16,45 +42,49 @@ struct UIGameOverButton;
 struct UIGameOverText;
 
 pub fn event_game_over(
+    mut commands: Commands,
     mut ev_game_over: EventReader<GameOverEvent>,
-    mut q_ui_game_over: Query<(&mut Visibility, &UIGameOver)>,
     mut next_state: ResMut<NextState<GameState>>,
     audio: Res<Audio>,
     audio_assets: Res<AudioAssets>,
-    mut text_query: Query<(&mut Text, &UIGameOverText)>,
 ) {
-    for _ in ev_game_over.read() {
-        for (mut visibility, _) in q_ui_game_over.iter_mut() {
-            *visibility = Visibility::Visible;
-        }
-
-        audio.play(audio_assets.gameover.clone()).with_volume(0.5);
-        next_state.set(GameState::GameOver);
-        for (mut text_component, _) in text_query.iter_mut() {
-            text_component.sections[0].value = "  JUEGO\nTERMINADO".to_string();
-        }
+    // several hits can kill the player in the same frame
+    if ev_game_over.read().count() == 0 {
+        return;
     }
+
+    audio.play(audio_assets.gameover.clone()).with_volume(0.5);
+    commands.insert_resource(RunOutcome::Died);
+    next_state.set(GameState::GameOver);
 }
 
 pub fn event_game_won(
-    mut ev_game_over: EventReader<GameWinEvent>,
-    mut q_ui_game_over: Query<(&mut Visibility, &UIGameOver)>,
+    mut commands: Commands,
+    mut ev_game_won: EventReader<GameWinEvent>,
     mut next_state: ResMut<NextState<GameState>>,
-    audio: Res<Audio>,
-    audio_assets: Res<AudioAssets>,
-    mut text_query: Query<(&mut Text, &UIGameOverText)>,
 ) {
-    for _ in ev_game_over.read() {
-        for (mut visibility, _) in q_ui_game_over.iter_mut() {
-            *visibility = Visibility::Visible;
-        }
-
-        // audio.play(audio_assets.gameover.clone()).with_volume(0.5);
-        next_state.set(GameState::GameOver);
-        for (mut text_component, _) in text_query.iter_mut() {
-            text_component.sections[0].value = "  JUEGO\nGANADO".to_string();
-        }
+    if ev_game_won.read().count() == 0 {
+        return;
     }
+
+    commands.insert_resource(RunOutcome::Won);
+    next_state.set(GameState::GameOver);
+}
+
+/// Time survived, kills, score and waves of the run that just ended.
+fn results_summary(run_stats: &RunStats, score: &Score, gameplay_state: &GameplayState) -> String {
+    let seconds = run_stats.time_survived.as_secs();
+    let kills: u32 = run_stats.enemies_killed.values().sum();
+
+    format!(
+        "TIME {:02}:{:02}   KILLS {}   SCORE {}   WAVE {}/{}",
+        seconds / 60,
+        seconds % 60,
+        kills,
+        score.score,
+        gameplay_state.wave_number.unwrap_or(0) + 1,
+        gameplay_state.waves.len(),
+    )
 }
 
 pub(crate) fn despawn_ui(mut commands: Commands, query: Query<Entity, With<UIGameOver>>) {
@@ -63,8 +93,16 @@ pub(crate) fn despawn_ui(mut commands: Commands, query: Query<Entity, With<UIGam
     }
 }
 
-pub(crate) fn draw_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
-    print!("draw game over");
+pub(crate) fn draw_ui(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    rng: Res<GameRng>,
+    run_outcome: Option<Res<RunOutcome>>,
+    run_stats: Res<RunStats>,
+    score: Res<Score>,
+    gameplay_state: Res<GameplayState>,
+) {
+    let run_outcome = run_outcome.map(|o| *o).unwrap_or(RunOutcome::Died);
 
     // game over
     commands
@@ -86,7 +124,7 @@ pub(crate) fn draw_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
         .with_children(|parent| {
             parent.spawn((
                 TextBundle::from_section(
-                    "  JUEGO\nTERMINADO",
+                    run_outcome.title(),
                     TextStyle {
                         font: font_assets.pixeloid_mono.clone(),
                         font_size: 100.0,
@@ -96,6 +134,40 @@ pub(crate) fn draw_ui(mut commands: Commands, font_assets: Res<FontAssets>) {
                 UIGameOverText,
             ));
 
+            parent.spawn((
+                TextBundle::from_section(
+                    format!("SEED: {}", rng.seed),
+                    TextStyle {
+                        font: font_assets.pixeloid_mono.clone(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(20.0),
+                    ..default()
+                }),
+                Name::new("ui game over seed"),
+            ));
+
+            parent.spawn((
+                TextBundle::from_section(
+                    results_summary(&run_stats, &score, &gameplay_state),
+                    TextStyle {
+                        font: font_assets.pixeloid_mono.clone(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(230.0),
+                    ..default()
+                }),
+                Name::new("ui game over results"),
+            ));
+
             parent
                 .spawn((
                     ButtonBundle {
@@ -168,7 +240,10 @@ impl Plugin for GameOverPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(
             Update,
-            (event_game_over, event_game_won).run_if(in_state(GameState::GamePlay)),
+            // dying in the frame the last wave is cleared is still a loss
+            (event_game_won, event_game_over)
+                .chain()
+                .run_if(in_state(GameState::GamePlay)),
         )
         .add_systems(
             Update,
@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::weapons::weapon::WeaponKind;
+use crate::ui::{UIPlayerExperience, UIPlayerLevel};
+
+/// Pickup radius of XP gems without the magnet perk.
+const BASE_PICKUP_RADIUS: f32 = 40.0;
+
+/// Level and experience of the player in the current run.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct Experience {
+    pub level: u32,
+    pub xp: u32,
+    /// Level-ups whose perk hasn't been chosen yet.
+    pub pending_level_ups: u32,
+}
+
+impl Default for Experience {
+    fn default() -> Self {
+        Experience {
+            level: 1,
+            xp: 0,
+            pending_level_ups: 0,
+        }
+    }
+}
+
+impl Experience {
+    /// XP needed to go from the current level to the next one.
+    pub fn xp_to_next_level(&self) -> u32 {
+        5 * self.level
+    }
+
+    pub fn add_xp(&mut self, xp: u32) {
+        self.xp += xp;
+
+        while self.xp >= self.xp_to_next_level() {
+            self.xp -= self.xp_to_next_level();
+            self.level += 1;
+            self.pending_level_ups += 1;
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Perk {
+    Damage,
+    AttackSpeed,
+    MoveSpeed,
+    Magnet,
+    Heal,
+    Armor,
+    /// A new weapon, or a level of one already carried.
+    Weapon(WeaponKind),
+}
+
+impl Perk {
+    pub const ALL: [Perk; 10] = [
+        Perk::Damage,
+        Perk::AttackSpeed,
+        Perk::MoveSpeed,
+        Perk::Magnet,
+        Perk::Heal,
+        Perk::Armor,
+        Perk::Weapon(WeaponKind::Machete),
+        Perk::Weapon(WeaponKind::Speargun),
+        Perk::Weapon(WeaponKind::Bow),
+        Perk::Weapon(WeaponKind::Pill),
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Perk::Damage => "DAMAGE",
+            Perk::AttackSpeed => "ATTACK SPEED",
+            Perk::MoveSpeed => "SPEED",
+            Perk::Magnet => "MAGNET",
+            Perk::Heal => "PIZZA PARTY",
+            Perk::Armor => "ARMOR",
+            Perk::Weapon(kind) => kind.name(),
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Perk::Damage => "+25% weapon damage",
+            Perk::AttackSpeed => "+15% attack rate",
+            Perk::MoveSpeed => "+10% movement speed",
+            Perk::Magnet => "+40 XP pickup radius",
+            Perk::Heal => "restore 30 health",
+            Perk::Armor => "-1 damage taken per hit",
+            Perk::Weapon(_) => "new weapon or +1 weapon level",
+        }
+    }
+}
+
+/// Perks taken in the current run and how many times each.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Perks {
+    pub levels: HashMap<Perk, u32>,
+}
+
+impl Perks {
+    pub fn level(&self, perk: Perk) -> u32 {
+        self.levels.get(&perk).copied().unwrap_or(0)
+    }
+
+    pub fn add(&mut self, perk: Perk) {
+        *self.levels.entry(perk).or_default() += 1;
+    }
+
+    pub fn damage_multiplier(&self) -> f32 {
+        1.0 + 0.25 * self.level(Perk::Damage) as f32
+    }
+
+    pub fn attack_speed_multiplier(&self) -> f32 {
+        1.0 + 0.15 * self.level(Perk::AttackSpeed) as f32
+    }
+
+    pub fn move_speed_multiplier(&self) -> f32 {
+        1.0 + 0.1 * self.level(Perk::MoveSpeed) as f32
+    }
+
+    pub fn pickup_radius(&self) -> f32 {
+        BASE_PICKUP_RADIUS + 40.0 * self.level(Perk::Magnet) as f32
+    }
+
+    pub fn armor(&self) -> u16 {
+        self.level(Perk::Armor) as u16
+    }
+}
+
+pub fn reset_experience(mut experience: ResMut<Experience>, mut perks: ResMut<Perks>) {
+    *experience = Experience::default();
+    *perks = Perks::default();
+}
+
+pub fn ui_experience_bar(
+    experience: Res<Experience>,
+    mut q_experience_bar: Query<&mut Style, With<UIPlayerExperience>>,
+    mut q_level_text: Query<&mut Text, With<UIPlayerLevel>>,
+) {
+    if !experience.is_changed() {
+        return;
+    }
+
+    let progress = experience.xp as f32 / experience.xp_to_next_level() as f32;
+    for mut style in q_experience_bar.iter_mut() {
+        style.width = Val::Percent(100.0 * progress);
+    }
+
+    for mut text in q_level_text.iter_mut() {
+        text.sections[0].value = format!("LV {}", experience.level);
+    }
+}
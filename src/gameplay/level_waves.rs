@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use bevy::asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext};
+use bevy::prelude::*;
+use bevy::utils::BoxedFuture;
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+
+use super::waves::Wave;
+
+// -----
+// Asset
+// -----
+
+/// Wave script of a single LDtk level, loaded from `assets/waves/*.waves.ron`.
+#[derive(Asset, TypePath, Deserialize, Clone, Debug)]
+pub struct LevelWaves {
+    /// Matches the `LevelID` field of the LDtk level.
+    pub level_id: usize,
+    pub waves: Vec<Wave>,
+}
+
+impl LevelWaves {
+    fn validate(&self) -> Result<(), String> {
+        if self.waves.is_empty() {
+            return Err(format!("level {} has no waves", self.level_id));
+        }
+
+        for (i, wave) in self.waves.iter().enumerate() {
+            if wave.events.is_empty() {
+                return Err(format!("wave {} has no events", i + 1));
+            }
+            if wave.event_duration.is_zero() {
+                return Err(format!("wave {} has zero event_duration", i + 1));
+            }
+            if wave.wave_duration.is_zero() {
+                return Err(format!("wave {} has zero wave_duration", i + 1));
+            }
+            if let Some(entry) = wave.events.iter().find(|e| e.count() == 0) {
+                return Err(format!("wave {} has an empty entry {:?}", i + 1, entry));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Durations are written as seconds in wave scripts.
+pub(crate) fn deserialize_secs<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let secs = f32::deserialize(deserializer)?;
+    if !secs.is_finite() || secs < 0.0 {
+        return Err(serde::de::Error::custom(format!(
+            "expected a non-negative number of seconds, got {}",
+            secs
+        )));
+    }
+    Ok(Duration::from_secs_f32(secs))
+}
+
+// ------
+// Loader
+// ------
+
+#[derive(Debug, Error)]
+pub enum LevelWavesLoaderError {
+    #[error("could not read wave script: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not parse wave script: {0}")]
+    Ron(#[from] ron::error::SpannedError),
+    #[error("invalid wave script: {0}")]
+    Invalid(String),
+}
+
+#[derive(Default)]
+pub struct LevelWavesLoader;
+
+impl AssetLoader for LevelWavesLoader {
+    type Asset = LevelWaves;
+    type Settings = ();
+    type Error = LevelWavesLoaderError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let level_waves = ron::de::from_bytes::<LevelWaves>(&bytes)?;
+            level_waves
+                .validate()
+                .map_err(LevelWavesLoaderError::Invalid)?;
+
+            Ok(level_waves)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["waves.ron"]
+    }
+}
@@ -1,5 +1,9 @@
 use bevy::prelude::*;
 
+use crate::{rng::GameRngSet, GameState};
+
+pub mod experience;
+pub mod level_waves;
 pub mod scoring;
 pub mod waves;
 
@@ -7,16 +11,38 @@ pub struct GameplayPlugin;
 
 impl Plugin for GameplayPlugin {
     fn build(&self, app: &mut App) {
-        app.init_resource::<waves::GameplayState>()
+        app.init_asset::<level_waves::LevelWaves>()
+            .init_asset_loader::<level_waves::LevelWavesLoader>()
+            .init_resource::<waves::GameplayState>()
             .init_resource::<scoring::Score>()
+            .init_resource::<scoring::RunStats>()
+            .init_resource::<experience::Experience>()
+            .init_resource::<experience::Perks>()
+            .add_systems(
+                OnEnter(GameState::GamePlay),
+                (scoring::reset_score, experience::reset_experience),
+            )
+            .add_systems(
+                Update,
+                (
+                    waves::event_on_level_change.in_set(GameRngSet::LevelChange),
+                    waves::hot_reload_level_waves.in_set(GameRngSet::HotReloadWaves),
+                    scoring::tick_run_stats,
+                    waves::check_game_won_or_lost.after(waves::event_wave),
+                )
+                    .run_if(in_state(GameState::GamePlay)),
+            )
             .add_systems(
                 Update,
                 (
-                    waves::event_on_level_change,
-                    waves::event_wave,
+                    // wave events are spawned by the spawn sets of the same frame
+                    waves::event_wave
+                        .after(GameRngSet::WaveTimers)
+                        .before(GameRngSet::SpawnEnemies),
                     waves::ui_wave_info_text,
                     scoring::ui_score_text,
-                    waves::handle_timers,
+                    experience::ui_experience_bar,
+                    waves::handle_timers.in_set(GameRngSet::WaveTimers),
                 ),
             )
             // Handle game over
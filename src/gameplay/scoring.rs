@@ -1,5 +1,9 @@
+use std::time::Duration;
+
 use bevy::prelude::*;
+use bevy::utils::HashMap;
 
+use crate::entities::characters::enemy_archetype::EnemyArchetypeId;
 use crate::ui::UIHighscore;
 
 #[derive(Resource, Default)]
@@ -7,6 +11,24 @@ pub struct Score {
     pub score: u32,
 }
 
+/// Statistics of the current run, kept after game over for the high-score table
+/// and the headless summary.
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub time_survived: Duration,
+    pub damage_taken: u32,
+    pub enemies_killed: HashMap<EnemyArchetypeId, u32>,
+}
+
+pub fn reset_score(mut score: ResMut<Score>, mut run_stats: ResMut<RunStats>) {
+    *score = Score::default();
+    *run_stats = RunStats::default();
+}
+
+pub fn tick_run_stats(mut run_stats: ResMut<RunStats>, time: Res<Time>) {
+    run_stats.time_survived += time.delta();
+}
+
 pub fn ui_score_text(mut text_query: Query<(&mut Text, &UIHighscore)>, score: Res<Score>) {
     if !score.is_changed() {
         return;
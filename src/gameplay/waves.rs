@@ -2,27 +2,54 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
 
-use crate::entities::characters::enemy::{Enemy, EnemyType, SpawnEnemyEvent};
+use crate::entities::characters::enemy::{Enemy, SpawnEnemyEvent};
+use crate::entities::characters::enemy_archetype::EnemyArchetypeId;
+use crate::entities::characters::enemy_spawn::SpawnStrategy;
 use crate::entities::items::item::ItemType;
-use crate::gameover::{GameOverEvent, GameWinEvent};
+use crate::gameover::GameWinEvent;
 use crate::ldtk::LevelChangeEvent;
-use crate::GameState;
+use crate::loading::WaveAssets;
+use crate::rng::GameRng;
 use crate::{entities::items::item::SpawnItemEvent, ui::*};
 
-#[derive(Clone)]
+use super::level_waves::{deserialize_secs, LevelWaves};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum WaveEntry {
-    Mierda { count: usize },
-    Pizza { count: usize },
-    Pendejo { count: usize },
-    Biboran { count: usize },
-    Boss { count: usize },
+    /// `count` enemies of an archetype from the archetype table, placed by
+    /// an optional `spawn` strategy, the off-screen ring by default.
+    Enemy {
+        archetype: EnemyArchetypeId,
+        count: usize,
+        #[serde(default)]
+        spawn: SpawnStrategy,
+    },
+    Pizza {
+        count: usize,
+    },
+    Biboran {
+        count: usize,
+    },
 }
 
-#[derive(Clone)]
+impl WaveEntry {
+    pub fn count(&self) -> usize {
+        match self {
+            WaveEntry::Enemy { count, .. }
+            | WaveEntry::Pizza { count }
+            | WaveEntry::Biboran { count } => *count,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
 pub struct Wave {
     pub events: Vec<WaveEntry>,
+    #[serde(deserialize_with = "deserialize_secs")]
     pub event_duration: Duration,
+    #[serde(deserialize_with = "deserialize_secs")]
     pub wave_duration: Duration,
 }
 
@@ -30,6 +57,7 @@ pub struct Wave {
 pub struct GameplayState {
     pub wave_number: Option<usize>,
     pub current_level_id: Option<usize>,
+    pub waves: Vec<Wave>,
     pub event_queue: Vec<WaveEntry>,
     pub wave_timer: Timer,
     pub wave_event_timer: Timer,
@@ -44,7 +72,7 @@ pub struct WaveEvent {
 impl GameplayState {
     pub fn current_level_waves(&self) -> Option<Vec<Wave>> {
         match self.current_level_id {
-            Some(1) => Some(get_level_1_waves()),
+            Some(_) if !self.waves.is_empty() => Some(self.waves.clone()),
             _ => None,
         }
     }
@@ -59,36 +87,37 @@ impl GameplayState {
         }
     }
 
-    pub fn select_random_wave_entry(&mut self) -> Option<WaveEntry> {
+    pub fn select_random_wave_entry(&mut self, rng: &mut impl Rng) -> Option<WaveEntry> {
         match self.current_wave() {
-            Some(wave) => {
-                let mut rng = rand::thread_rng();
-
+            Some(_) => {
                 if self.event_queue.is_empty() {
                     return None;
                 }
 
                 let random_index = rng.gen_range(0..self.event_queue.len());
-                // remove entry with index random_index from event_queue
-                self.event_queue = self
-                    .event_queue
-                    .iter()
-                    .enumerate()
-                    .filter(|(i, _)| *i != random_index)
-                    .map(|(_, v)| v.clone())
-                    .collect();
-
-                let wave_entry = wave.events.get(random_index).unwrap();
-                Some(wave_entry.clone())
+                Some(self.event_queue.remove(random_index))
             }
             _ => None,
         }
     }
+
+    /// Resets the event queue and wave timer to the beginning of `wave_number`
+    /// and draws its first entry.
+    pub fn start_wave(&mut self, wave_number: usize, rng: &mut impl Rng) -> Option<WaveEntry> {
+        let wave = self.waves.get(wave_number)?.clone();
+
+        self.wave_number = Some(wave_number);
+        self.event_queue = wave.events;
+        self.wave_timer = Timer::new(wave.wave_duration, TimerMode::Once);
+
+        self.select_random_wave_entry(rng)
+    }
 }
 
 pub fn handle_timers(
     mut gameplay_state: ResMut<GameplayState>,
     mut ew_wave: EventWriter<WaveEvent>,
+    mut rng: ResMut<GameRng>,
     time: Res<Time>,
 ) {
     gameplay_state.wave_timer.tick(time.delta());
@@ -100,18 +129,10 @@ pub fn handle_timers(
             let mut current_wave_number = gameplay_state.wave_number.unwrap_or(0);
             if current_wave_number < max_wave_number {
                 current_wave_number += 1;
-                gameplay_state.wave_number = Some(current_wave_number);
-                gameplay_state.event_queue = current_level_waves
-                    [gameplay_state.wave_number.unwrap()]
-                .events
-                .clone();
-
-                gameplay_state.wave_timer = Timer::new(
-                    gameplay_state.current_wave().unwrap().wave_duration,
-                    TimerMode::Once,
-                );
-
-                let wave_event = gameplay_state.select_random_wave_entry().unwrap();
+
+                let wave_event = gameplay_state
+                    .start_wave(current_wave_number, &mut *rng)
+                    .unwrap();
                 ew_wave.send(WaveEvent {
                     wave_number: current_wave_number,
                     wave_entry: wave_event,
@@ -121,7 +142,7 @@ pub fn handle_timers(
     }
 
     if gameplay_state.wave_event_timer.just_finished() {
-        let wave_event = gameplay_state.select_random_wave_entry();
+        let wave_event = gameplay_state.select_random_wave_entry(&mut *rng);
         if wave_event.is_none() {
             return;
         }
@@ -132,58 +153,116 @@ pub fn handle_timers(
     }
 }
 
-#[allow(clippy::single_match)]
 pub fn event_on_level_change(
     mut er_on_level_change: EventReader<LevelChangeEvent>,
     mut gameplay_state: ResMut<GameplayState>,
+    wave_assets: Res<WaveAssets>,
+    level_waves_assets: Res<Assets<LevelWaves>>,
+    mut rng: ResMut<GameRng>,
 
     mut ew_wave: EventWriter<WaveEvent>,
 ) {
     for event in er_on_level_change.read() {
-        match event.level_id {
-            1 => {
-                let waves = get_level_1_waves();
+        let level_waves = wave_assets
+            .levels
+            .iter()
+            .filter_map(|handle| level_waves_assets.get(handle))
+            .find(|level_waves| level_waves.level_id == event.level_id);
 
-                *gameplay_state = GameplayState {
-                    wave_number: Some(0),
-                    current_level_id: Some(1),
-                    event_queue: waves[0].events.clone(),
-                    ..default()
-                };
+        let Some(level_waves) = level_waves else {
+            warn!("no wave script found for level {}", event.level_id);
+            continue;
+        };
 
-                let wave_entry = gameplay_state.select_random_wave_entry().unwrap();
+        *gameplay_state = GameplayState {
+            current_level_id: Some(level_waves.level_id),
+            waves: level_waves.waves.clone(),
+            ..default()
+        };
 
-                ew_wave.send(WaveEvent {
-                    wave_number: 1,
-                    wave_entry,
-                });
+        let wave_entry = gameplay_state.start_wave(0, &mut *rng).unwrap();
 
-                gameplay_state.wave_timer = Timer::new(
-                    gameplay_state.current_wave().unwrap().wave_duration,
-                    TimerMode::Once,
-                );
-            }
-            _ => {}
+        ew_wave.send(WaveEvent {
+            wave_number: 1,
+            wave_entry,
+        });
+    }
+}
+
+/// Picks up edits of the current level's wave script while the game runs.
+/// The current wave is restarted with the new definition, earlier waves are not replayed.
+pub fn hot_reload_level_waves(
+    mut er_level_waves: EventReader<AssetEvent<LevelWaves>>,
+    mut gameplay_state: ResMut<GameplayState>,
+    level_waves_assets: Res<Assets<LevelWaves>>,
+    mut rng: ResMut<GameRng>,
+
+    mut ew_wave: EventWriter<WaveEvent>,
+) {
+    for event in er_level_waves.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+
+        let Some(level_waves) = level_waves_assets.get(*id) else {
+            continue;
+        };
+
+        if gameplay_state.current_level_id != Some(level_waves.level_id) {
+            continue;
+        }
+
+        let wave_number = gameplay_state
+            .wave_number
+            .unwrap_or(0)
+            .min(level_waves.waves.len() - 1);
+
+        gameplay_state.waves = level_waves.waves.clone();
+
+        info!(
+            "wave script for level {} reloaded, restarting wave {}",
+            level_waves.level_id,
+            wave_number + 1
+        );
+
+        if let Some(wave_entry) = gameplay_state.start_wave(wave_number, &mut *rng) {
+            ew_wave.send(WaveEvent {
+                wave_number,
+                wave_entry,
+            });
         }
     }
 }
 
+/// The run is won once the last wave has sent all of its entries and every
+/// enemy, boss included, is dead. Death is sent by `event_player_hit`.
 pub fn check_game_won_or_lost(
-    mut next_state: ResMut<NextState<GameState>>,
     gameplay_state: Res<GameplayState>,
-    query: Query<&Enemy>,
+    q_enemies: Query<&Enemy>,
     mut ev_game_won: EventWriter<GameWinEvent>,
 ) {
-    // check if current wave is last
-    if let Some(current_wave) = gameplay_state.current_wave() {
-        if gameplay_state.wave_number.unwrap()
-            == (gameplay_state.current_level_waves().unwrap().len() - 1)
-        {
-            // check if all enemies are dead
-            if query.iter().count() == 0 {
-                ev_game_won.send(GameWinEvent);
-            }
-        }
+    let (Some(waves), Some(wave_number)) = (
+        gameplay_state.current_level_waves(),
+        gameplay_state.wave_number,
+    ) else {
+        return;
+    };
+
+    if wave_number != waves.len() - 1 || !gameplay_state.event_queue.is_empty() {
+        return;
+    }
+
+    // the timer restarts when the last entry is sent, its enemies are out by the time it ends
+    if !gameplay_state.wave_event_timer.finished() {
+        return;
+    }
+
+    let enemies_alive = q_enemies
+        .iter()
+        .any(|enemy| !enemy.is_dummy && !enemy.marked_for_despawn);
+
+    if !enemies_alive {
+        ev_game_won.send(GameWinEvent);
     }
 }
 
@@ -195,41 +274,30 @@ pub fn event_wave(
     mut ev_item_spawn: EventWriter<SpawnItemEvent>,
 ) {
     for event in er_on_wave_change.read() {
-        match event.wave_entry {
-            WaveEntry::Mierda { count } => {
-                ev_enemy_spawn.send(SpawnEnemyEvent {
-                    count: count as u32,
-                    enemy_type: EnemyType::Mierda,
-                });
-            }
-            WaveEntry::Pendejo { count } => {
+        match &event.wave_entry {
+            WaveEntry::Enemy {
+                archetype,
+                count,
+                spawn,
+            } => {
                 ev_enemy_spawn.send(SpawnEnemyEvent {
-                    count: count as u32,
-                    enemy_type: EnemyType::Pendejo,
+                    count: *count as u32,
+                    archetype: archetype.clone(),
+                    strategy: *spawn,
                 });
             }
             WaveEntry::Pizza { count } => {
                 ev_item_spawn.send(SpawnItemEvent {
-                    count: count as u32,
+                    count: *count as u32,
                     item_type: ItemType::Pizza,
                 });
             }
             WaveEntry::Biboran { count } => {
                 ev_item_spawn.send(SpawnItemEvent {
-                    count: count as u32,
+                    count: *count as u32,
                     item_type: ItemType::Biboran,
                 });
             }
-            WaveEntry::Boss { count } => {
-                ev_enemy_spawn.send(SpawnEnemyEvent {
-                    count: count as u32,
-                    enemy_type: EnemyType::Psychiatrist1,
-                });
-                ev_enemy_spawn.send(SpawnEnemyEvent {
-                    count: count as u32,
-                    enemy_type: EnemyType::Psychiatrist2,
-                });
-            }
         }
 
         gameplay_state.wave_event_timer = Timer::new(
@@ -257,42 +325,3 @@ pub fn ui_wave_info_text(
         );
     }
 }
-
-#[allow(dead_code)]
-pub fn get_level_1_waves() -> Vec<Wave> {
-    vec![
-        Wave {
-            events: vec![WaveEntry::Mierda { count: 100 }],
-            event_duration: Duration::from_secs(10),
-            wave_duration: Duration::from_secs(10),
-        },
-        Wave {
-            events: vec![
-                WaveEntry::Mierda { count: 100 },
-                WaveEntry::Pizza { count: 5 },
-                WaveEntry::Mierda { count: 100 },
-                WaveEntry::Biboran { count: 5 },
-                WaveEntry::Mierda { count: 100 },
-            ],
-            event_duration: Duration::from_secs(10),
-            wave_duration: Duration::from_secs(40),
-        },
-        Wave {
-            events: vec![
-                WaveEntry::Pendejo { count: 100 },
-                WaveEntry::Pizza { count: 3 },
-                WaveEntry::Pendejo { count: 100 },
-                WaveEntry::Pizza { count: 3 },
-                WaveEntry::Pendejo { count: 100 },
-                WaveEntry::Pizza { count: 3 },
-            ],
-            event_duration: Duration::from_secs(5),
-            wave_duration: Duration::from_secs(60),
-        },
-        Wave {
-            events: vec![WaveEntry::Boss { count: 1 }],
-            event_duration: Duration::from_secs(5),
-            wave_duration: Duration::from_secs(120),
-        },
-    ]
-}
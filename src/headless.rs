@@ -0,0 +1,352 @@
+use std::time::{Duration, Instant};
+
+use bevy::app::{AppExit, PluginGroupBuilder, ScheduleRunnerPlugin};
+use bevy::log::LogPlugin;
+use bevy::prelude::*;
+use bevy::render::settings::WgpuSettings;
+use bevy::render::RenderPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+
+use crate::cli::CommandLineArgs;
+use crate::controls::{self, ControlEvent};
+use crate::entities::characters::enemy::{Enemy, SpawnEnemyEvent};
+use crate::entities::characters::enemy_archetype::{EnemyArchetypeTable, EnemyBehaviour};
+use crate::entities::characters::enemy_spawn::SpawnStrategy;
+use crate::entities::player::Player;
+use crate::gameover::RunOutcome;
+use crate::gameplay::experience::Experience;
+use crate::gameplay::scoring::{RunStats, Score};
+use crate::gameplay::waves::GameplayState;
+use crate::levelup::{self, ChoosePerkEvent, PerkOffers};
+use crate::pause::PauseState;
+use crate::replay::{skip_to_gameplay, use_fixed_physics_timestep, REPLAY_TIMESTEP};
+use crate::rng::GameRng;
+use crate::GameState;
+
+/// Game time after which a headless run is stopped when `--time-limit` is not given.
+const DEFAULT_TIME_LIMIT: Duration = Duration::from_secs(600);
+/// Enemies closer than this push the bot away, the closer the harder.
+const BOT_FLEE_RADIUS: f32 = 150.0;
+/// Distance from its spawn point at which the bot is pulled back as hard as
+/// one enemy right next to it pushes, keeps it out of the corners.
+const BOT_HOME_RADIUS: f32 = 200.0;
+
+/// Frames timed by `--crowd-bench` once its enemies are spawned.
+const CROWD_BENCH_FRAMES: usize = 300;
+/// Frame time of 60 FPS, what a crowd bench should stay under.
+const CROWD_BENCH_TARGET: Duration = Duration::from_micros(16_667);
+
+/// `DefaultPlugins` without a window or a renderer, updating as fast as possible.
+pub fn headless_plugins() -> PluginGroupBuilder {
+    DefaultPlugins
+        .build()
+        .disable::<WinitPlugin>()
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .set(RenderPlugin {
+            render_creation: WgpuSettings {
+                backends: None,
+                ..default()
+            }
+            .into(),
+        })
+        .set(LogPlugin {
+            filter: "warn,legend_of_mierda=info".into(),
+            level: bevy::log::Level::INFO,
+        })
+        .add(ScheduleRunnerPlugin::run_loop(Duration::ZERO))
+}
+
+// ---------
+// Resources
+// ---------
+
+#[derive(Resource)]
+pub struct HeadlessRun {
+    pub time_limit: Duration,
+}
+
+/// Enemies spawned by `--crowd-bench` and the wall-clock time of the frames since.
+#[derive(Resource)]
+pub struct CrowdBench {
+    pub count: u32,
+    spawned: bool,
+    last_frame: Option<Instant>,
+    frame_times: Vec<Duration>,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Outcome {
+    Died,
+    Won,
+    TimeLimit,
+}
+
+// -------
+// Systems
+// -------
+
+/// Runs away from nearby enemies and drifts back towards its spawn point when none are close.
+fn bot_controls(
+    mut home: Local<Option<Vec2>>,
+    q_player: Query<&GlobalTransform, With<Player>>,
+    q_enemies: Query<(&GlobalTransform, &Enemy)>,
+    mut ev_control: EventWriter<ControlEvent>,
+) {
+    let Ok(player_transform) = q_player.get_single() else {
+        return;
+    };
+
+    let position = player_transform.translation().truncate();
+    let home = *home.get_or_insert(position);
+
+    let mut direction = (home - position) / BOT_HOME_RADIUS;
+    for (enemy_transform, enemy) in q_enemies.iter() {
+        if enemy.is_dummy {
+            continue;
+        }
+
+        let away = position - enemy_transform.translation().truncate();
+        let distance = away.length();
+        if distance < BOT_FLEE_RADIUS {
+            direction += away.normalize_or_zero() * (1.0 - distance / BOT_FLEE_RADIUS);
+        }
+    }
+
+    // only move along an axis that is at least ~22.5 degrees off the direction
+    let direction = direction.normalize_or_zero();
+    ev_control.send(ControlEvent {
+        up: direction.y > 0.38,
+        down: direction.y < -0.38,
+        left: direction.x < -0.38,
+        right: direction.x > 0.38,
+        ..default()
+    });
+}
+
+/// Takes the first perk offered, they are shuffled with the run seed.
+fn bot_choose_perk(perk_offers: Res<PerkOffers>, mut ev_choose_perk: EventWriter<ChoosePerkEvent>) {
+    if let Some(perk) = perk_offers.0.first() {
+        ev_choose_perk.send(ChoosePerkEvent { perk: *perk });
+    }
+}
+
+fn check_time_limit(
+    headless_run: Res<HeadlessRun>,
+    run_stats: Res<RunStats>,
+    score: Res<Score>,
+    experience: Res<Experience>,
+    gameplay_state: Res<GameplayState>,
+    rng: Res<GameRng>,
+    mut ev_app_exit: EventWriter<AppExit>,
+) {
+    if run_stats.time_survived < headless_run.time_limit {
+        return;
+    }
+
+    print_summary(
+        Outcome::TimeLimit,
+        &run_stats,
+        &score,
+        &experience,
+        &gameplay_state,
+        &rng,
+    );
+    ev_app_exit.send(AppExit);
+}
+
+/// Spawns the bench's enemies along the level edges once the player is there,
+/// then times `CROWD_BENCH_FRAMES` frames, prints their frame times and exits.
+fn run_crowd_bench(
+    mut bench: ResMut<CrowdBench>,
+    archetypes: Res<EnemyArchetypeTable>,
+    q_player: Query<(), With<Player>>,
+    q_enemies: Query<&Enemy>,
+    mut ev_spawn_enemy: EventWriter<SpawnEnemyEvent>,
+    mut ev_app_exit: EventWriter<AppExit>,
+) {
+    if !bench.spawned {
+        if q_player.is_empty() {
+            return;
+        }
+
+        let chaser = archetypes
+            .archetypes
+            .iter()
+            .filter(|(_, archetype)| matches!(archetype.behaviour, EnemyBehaviour::Chase { .. }))
+            .map(|(id, _)| id)
+            .min();
+
+        let Some(chaser) = chaser else {
+            error!("crowd bench needs an enemy archetype that chases the player");
+            ev_app_exit.send(AppExit);
+            return;
+        };
+
+        ev_spawn_enemy.send(SpawnEnemyEvent {
+            count: bench.count,
+            archetype: chaser.clone(),
+            strategy: SpawnStrategy::LevelEdge,
+        });
+        bench.spawned = true;
+        return;
+    }
+
+    let now = Instant::now();
+    if let Some(last_frame) = bench.last_frame.replace(now) {
+        bench.frame_times.push(now - last_frame);
+    }
+
+    if bench.frame_times.len() < CROWD_BENCH_FRAMES {
+        return;
+    }
+
+    let mut frame_times = bench.frame_times.clone();
+    frame_times.sort();
+
+    let mean = frame_times.iter().sum::<Duration>() / frame_times.len() as u32;
+    let p99 = frame_times[frame_times.len() * 99 / 100];
+    let worst = frame_times[frame_times.len() - 1];
+    let enemies_alive = q_enemies.iter().filter(|enemy| !enemy.is_dummy).count();
+
+    let ms = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    println!("crowd bench: {} enemies spawned", bench.count);
+    println!("enemies alive: {}", enemies_alive);
+    println!("frames: {}", frame_times.len());
+    println!("mean frame: {:.2}ms", ms(mean));
+    println!("99th percentile frame: {:.2}ms", ms(p99));
+    println!("worst frame: {:.2}ms", ms(worst));
+    println!(
+        "within 60 FPS: {}",
+        if p99 <= CROWD_BENCH_TARGET {
+            "yes"
+        } else {
+            "no"
+        }
+    );
+    ev_app_exit.send(AppExit);
+}
+
+fn game_over_summary(
+    run_outcome: Option<Res<RunOutcome>>,
+    run_stats: Res<RunStats>,
+    score: Res<Score>,
+    experience: Res<Experience>,
+    gameplay_state: Res<GameplayState>,
+    rng: Res<GameRng>,
+    mut ev_app_exit: EventWriter<AppExit>,
+) {
+    let outcome = match run_outcome.map(|o| *o) {
+        Some(RunOutcome::Won) => Outcome::Won,
+        _ => Outcome::Died,
+    };
+
+    print_summary(
+        outcome,
+        &run_stats,
+        &score,
+        &experience,
+        &gameplay_state,
+        &rng,
+    );
+    ev_app_exit.send(AppExit);
+}
+
+fn print_summary(
+    outcome: Outcome,
+    run_stats: &RunStats,
+    score: &Score,
+    experience: &Experience,
+    gameplay_state: &GameplayState,
+    rng: &GameRng,
+) {
+    let wave_reached = gameplay_state.wave_number.unwrap_or(0) + 1;
+    let waves_survived = match outcome {
+        Outcome::Won => wave_reached,
+        Outcome::Died | Outcome::TimeLimit => wave_reached - 1,
+    };
+
+    let mut enemies_killed: Vec<_> = run_stats
+        .enemies_killed
+        .iter()
+        .map(|(archetype, count)| (archetype.to_string(), *count))
+        .collect();
+    enemies_killed.sort();
+
+    println!("outcome: {:?}", outcome);
+    println!("seed: {}", rng.seed);
+    println!(
+        "waves survived: {}/{}",
+        waves_survived,
+        gameplay_state.waves.len()
+    );
+    println!(
+        "time survived: {:.1}s",
+        run_stats.time_survived.as_secs_f32()
+    );
+    println!("damage taken: {}", run_stats.damage_taken);
+    println!("score: {}", score.score);
+    println!("level: {}", experience.level);
+    println!("enemies killed:");
+    for (archetype, count) in enemies_killed {
+        println!("  {}: {}", archetype, count);
+    }
+}
+
+// ------
+// Plugin
+// ------
+
+/// Plays a single run with a bot instead of the player's input, prints a
+/// summary and exits. Enabled with `--headless`.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        let args = app.world.resource::<CommandLineArgs>().clone();
+
+        app.insert_resource(HeadlessRun {
+            time_limit: args.time_limit.unwrap_or(DEFAULT_TIME_LIMIT),
+        })
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            REPLAY_TIMESTEP,
+        )))
+        .add_systems(Startup, use_fixed_physics_timestep)
+        .add_systems(OnEnter(GameState::Splash), skip_to_gameplay)
+        .add_systems(
+            Update,
+            (
+                bot_controls.before(controls::control_character),
+                check_time_limit,
+            )
+                .run_if(in_state(GameState::GamePlay)),
+        )
+        .add_systems(
+            Update,
+            bot_choose_perk
+                .before(levelup::event_choose_perk)
+                .run_if(in_state(PauseState::LevelUp)),
+        )
+        .add_systems(OnEnter(GameState::GameOver), game_over_summary);
+
+        if let Some(count) = args.crowd_bench {
+            app.insert_resource(CrowdBench {
+                count,
+                spawned: false,
+                last_frame: None,
+                frame_times: Vec::new(),
+            })
+            .add_systems(
+                Update,
+                run_crowd_bench
+                    .run_if(in_state(GameState::GamePlay))
+                    .run_if(in_state(PauseState::Running)),
+            );
+        }
+    }
+}
@@ -0,0 +1,457 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use serde::{Deserialize, Serialize};
+
+use crate::gameplay::scoring::{RunStats, Score};
+use crate::gameplay::waves::GameplayState;
+use crate::loading::FontAssets;
+use crate::replay::ReplayPlayer;
+use crate::rng::GameRng;
+use crate::ui::UIGameOver;
+use crate::{storage, ButtonColors, GameState};
+
+const HIGHSCORES_FILE: &str = "highscores.ron";
+const MAX_HIGHSCORES: usize = 10;
+const MAX_NAME_LENGTH: usize = 10;
+
+pub struct HighscoresPlugin;
+
+impl Plugin for HighscoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighscoreTable::load())
+            .add_systems(
+                OnEnter(GameState::GameOver),
+                (check_new_highscore, draw_gameover_highscores).chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    name_entry_input.run_if(resource_exists::<PendingHighscore>()),
+                    update_highscore_texts,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::GameOver)),
+            )
+            .add_systems(OnExit(GameState::GameOver), submit_pending_highscore)
+            .add_systems(
+                Update,
+                (
+                    open_highscores_menu,
+                    click_close_highscores_button,
+                    update_highscore_texts,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Menu)),
+            )
+            .add_systems(OnExit(GameState::Menu), close_highscores_menu);
+    }
+}
+
+// ----
+// Data
+// ----
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighscoreEntry {
+    pub name: String,
+    pub score: u32,
+    /// 1-based number of the last wave reached.
+    pub wave: usize,
+    /// Seconds survived.
+    pub time_survived: u64,
+    /// Seconds since the Unix epoch at the end of the run.
+    pub date: u64,
+    pub seed: u64,
+}
+
+/// Best runs sorted by score, saved to `highscores.ron` in the data directory.
+#[derive(Resource, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HighscoreTable {
+    pub entries: Vec<HighscoreEntry>,
+}
+
+impl HighscoreTable {
+    pub fn load() -> Self {
+        let mut table: HighscoreTable = storage::load(HIGHSCORES_FILE).unwrap_or_default();
+        table.entries.sort_by(|a, b| b.score.cmp(&a.score));
+        table.entries.truncate(MAX_HIGHSCORES);
+        table
+    }
+
+    pub fn save(&self) {
+        storage::save(HIGHSCORES_FILE, self);
+    }
+
+    /// Rank the score would get in the table, `None` if it doesn't make it.
+    /// Ties go below the existing entries.
+    pub fn rank(&self, score: u32) -> Option<usize> {
+        if score == 0 {
+            return None;
+        }
+
+        let rank = self.entries.iter().take_while(|e| e.score >= score).count();
+        (rank < MAX_HIGHSCORES).then_some(rank)
+    }
+
+    pub fn insert(&mut self, entry: HighscoreEntry) -> Option<usize> {
+        let rank = self.rank(entry.score)?;
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_HIGHSCORES);
+        Some(rank)
+    }
+}
+
+/// A run that made it into the table and waits for the player's name.
+#[derive(Resource)]
+struct PendingHighscore {
+    entry: HighscoreEntry,
+    rank: usize,
+}
+
+// ----------
+// Components
+// ----------
+
+#[derive(Component)]
+struct UIHighscoreTable;
+
+#[derive(Component)]
+struct HighscoresMenu;
+
+/// Put on a button to show the high-score table when it is pressed.
+#[derive(Component)]
+pub struct OpenHighscoresButton;
+
+#[derive(Component)]
+struct CloseHighscoresButton;
+
+// -------
+// Systems
+// -------
+
+fn check_new_highscore(
+    mut commands: Commands,
+    table: Res<HighscoreTable>,
+    score: Res<Score>,
+    run_stats: Res<RunStats>,
+    gameplay_state: Res<GameplayState>,
+    rng: Res<GameRng>,
+    replay_player: Option<Res<ReplayPlayer>>,
+) {
+    // replays re-run a recorded game, they don't set new records
+    if replay_player.is_some() {
+        return;
+    }
+
+    let Some(rank) = table.rank(score.score) else {
+        return;
+    };
+
+    let date = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    commands.insert_resource(PendingHighscore {
+        entry: HighscoreEntry {
+            name: String::new(),
+            score: score.score,
+            wave: gameplay_state.wave_number.unwrap_or(0) + 1,
+            time_survived: run_stats.time_survived.as_secs(),
+            date,
+            seed: rng.seed,
+        },
+        rank,
+    });
+}
+
+fn draw_gameover_highscores(mut commands: Commands, font_assets: Res<FontAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    top: Val::Px(50.0),
+                    ..default()
+                },
+                ..default()
+            },
+            UIGameOver,
+            Name::new("ui game over highscores"),
+        ))
+        .with_children(|parent| {
+            spawn_highscore_table(parent, &font_assets);
+        });
+}
+
+fn spawn_highscore_table(parent: &mut ChildBuilder, font_assets: &FontAssets) {
+    parent.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font: font_assets.pixeloid_mono.clone(),
+                font_size: 12.0,
+                color: Color::WHITE,
+            },
+        ),
+        UIHighscoreTable,
+    ));
+}
+
+fn name_entry_input(
+    mut commands: Commands,
+    mut ev_received_character: EventReader<ReceivedCharacter>,
+    keys: Res<Input<KeyCode>>,
+    mut pending: ResMut<PendingHighscore>,
+    mut table: ResMut<HighscoreTable>,
+) {
+    let name = &mut pending.entry.name;
+
+    for ev in ev_received_character.read() {
+        if (ev.char.is_ascii_alphanumeric() || ev.char == ' ') && name.len() < MAX_NAME_LENGTH {
+            name.push(ev.char.to_ascii_uppercase());
+        }
+    }
+
+    if keys.just_pressed(KeyCode::Back) {
+        name.pop();
+    }
+
+    if keys.just_pressed(KeyCode::Return) {
+        submit(&mut table, &pending);
+        commands.remove_resource::<PendingHighscore>();
+    }
+}
+
+fn submit_pending_highscore(
+    mut commands: Commands,
+    pending: Option<Res<PendingHighscore>>,
+    mut table: ResMut<HighscoreTable>,
+) {
+    if let Some(pending) = pending {
+        submit(&mut table, &pending);
+        commands.remove_resource::<PendingHighscore>();
+    }
+}
+
+fn submit(table: &mut HighscoreTable, pending: &PendingHighscore) {
+    let mut entry = pending.entry.clone();
+    entry.name = entry.name.trim().to_string();
+    if entry.name.is_empty() {
+        entry.name = "???".to_string();
+    }
+
+    table.insert(entry);
+    table.save();
+}
+
+fn update_highscore_texts(
+    table: Res<HighscoreTable>,
+    pending: Option<Res<PendingHighscore>>,
+    mut q_table_text: Query<&mut Text, With<UIHighscoreTable>>,
+) {
+    for mut text in q_table_text.iter_mut() {
+        let style = TextStyle {
+            color: Color::WHITE,
+            ..text.sections[0].style.clone()
+        };
+        let sections = highscore_sections(&table, pending.as_deref(), &style);
+
+        let unchanged = text.sections.len() == sections.len()
+            && text
+                .sections
+                .iter()
+                .zip(sections.iter())
+                .all(|(a, b)| a.value == b.value);
+
+        if !unchanged {
+            text.sections = sections;
+        }
+    }
+}
+
+fn highscore_sections(
+    table: &HighscoreTable,
+    pending: Option<&PendingHighscore>,
+    style: &TextStyle,
+) -> Vec<TextSection> {
+    let highlight = TextStyle {
+        color: Color::YELLOW,
+        ..style.clone()
+    };
+
+    let mut sections = vec![];
+
+    if pending.is_some() {
+        sections.push(TextSection::new(
+            "NEW RECORD! TYPE YOUR NAME AND PRESS ENTER\n\n",
+            highlight.clone(),
+        ));
+    }
+
+    sections.push(TextSection::new(
+        format!(
+            "    {:<11} {:>6} {:>4} {:>5} {:<10} {}\n",
+            "NAME", "SCORE", "WAVE", "TIME", "DATE", "SEED"
+        ),
+        style.clone(),
+    ));
+
+    let mut entries: Vec<(&HighscoreEntry, bool)> =
+        table.entries.iter().map(|entry| (entry, false)).collect();
+    if let Some(pending) = pending {
+        entries.insert(pending.rank, (&pending.entry, true));
+        entries.truncate(MAX_HIGHSCORES);
+    }
+
+    if entries.is_empty() {
+        sections.push(TextSection::new("NO RECORDS YET", style.clone()));
+    }
+
+    for (rank, &(entry, is_pending)) in entries.iter().enumerate() {
+        let name = if is_pending {
+            format!("{}_", entry.name)
+        } else {
+            entry.name.clone()
+        };
+
+        sections.push(TextSection::new(
+            format!(
+                "{:>2}. {:<11} {:>6} {:>4} {:>2}:{:02} {} {}\n",
+                rank + 1,
+                name,
+                entry.score,
+                entry.wave,
+                entry.time_survived / 60,
+                entry.time_survived % 60,
+                format_date(entry.date),
+                entry.seed,
+            ),
+            if is_pending {
+                highlight.clone()
+            } else {
+                style.clone()
+            },
+        ));
+    }
+
+    sections
+}
+
+/// `YYYY-MM-DD` (UTC) of a Unix timestamp.
+fn format_date(timestamp: u64) -> String {
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = (timestamp / 86400) as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn open_highscores_menu(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    q_open_button: Query<&Interaction, (Changed<Interaction>, With<OpenHighscoresButton>)>,
+    q_highscores_menu: Query<(), With<HighscoresMenu>>,
+) {
+    if !q_highscores_menu.is_empty() {
+        return;
+    }
+
+    if !q_open_button.iter().any(|i| *i == Interaction::Pressed) {
+        return;
+    }
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.9).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(100),
+                ..default()
+            },
+            HighscoresMenu,
+            Name::new("highscores menu"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "HIGH SCORES",
+                TextStyle {
+                    font: font_assets.pixeloid_mono.clone(),
+                    font_size: 60.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            spawn_highscore_table(parent, &font_assets);
+
+            parent
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(200.0),
+                            height: Val::Px(50.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..default()
+                        },
+                        background_color: Color::rgba_u8(0, 0, 0, 0).into(),
+                        ..default()
+                    },
+                    ButtonColors {
+                        normal: Color::WHITE,
+                        hovered: Color::YELLOW,
+                    },
+                    CloseHighscoresButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "BACK",
+                        TextStyle {
+                            font: font_assets.pixeloid_mono.clone(),
+                            font_size: 40.0,
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+        });
+}
+
+fn click_close_highscores_button(
+    commands: Commands,
+    q_close_button: Query<&Interaction, (Changed<Interaction>, With<CloseHighscoresButton>)>,
+    q_highscores_menu: Query<Entity, With<HighscoresMenu>>,
+) {
+    if q_close_button.iter().any(|i| *i == Interaction::Pressed) {
+        close_highscores_menu(commands, q_highscores_menu);
+    }
+}
+
+fn close_highscores_menu(
+    mut commands: Commands,
+    q_highscores_menu: Query<Entity, With<HighscoresMenu>>,
+) {
+    for entity in q_highscores_menu.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
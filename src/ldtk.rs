@@ -1,5 +1,6 @@
 use std::collections::{HashMap, HashSet};
 
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
@@ -7,10 +8,15 @@ use bevy_magic_light_2d::SpriteCamera;
 use bevy_rapier2d::prelude::*;
 
 use crate::entities::{
-    characters::enemy::{create_enemy_bundle, Enemy},
+    characters::{
+        enemy::{create_enemy_bundle, Enemy},
+        enemy_archetype::EnemyArchetypeTable,
+    },
     items::item::{create_item_bundle, Item},
     player::Player,
 };
+use crate::loading::LevelAssets;
+use crate::rng::GameRng;
 
 const ASPECT_RATIO: f32 = 1.0;
 pub const LEVEL_1_IID: &str = "d53f9950-c640-11ed-8430-4942c04951ff";
@@ -27,12 +33,94 @@ pub struct LevelChangeEvent {
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Wall;
 
+/// Collider spawned for a rectangle of `Wall` cells, stops projectiles.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct WallCollider;
+
 #[derive(Clone, Debug, Default, Bundle, LdtkIntCell)]
 pub struct WallBundle {
     wall: Wall,
     sensor: Sensor,
 }
 
+// Resources
+
+/// `Wall` cells of a level, in level-local grid coordinates.
+#[derive(Clone, Debug, Default)]
+pub struct WallGrid {
+    pub width: i32,
+    pub height: i32,
+    pub grid_size: i32,
+    walls: HashSet<GridCoords>,
+}
+
+impl WallGrid {
+    pub fn contains(&self, coords: GridCoords) -> bool {
+        coords.x >= 0 && coords.y >= 0 && coords.x < self.width && coords.y < self.height
+    }
+
+    /// Cells outside of the level count as walls.
+    pub fn is_wall(&self, coords: GridCoords) -> bool {
+        !self.contains(coords) || self.walls.contains(&coords)
+    }
+
+    pub fn is_wall_at(&self, position: Vec2) -> bool {
+        self.is_wall(self.cell_at(position))
+    }
+
+    /// Cell under a level-local `position`.
+    pub fn cell_at(&self, position: Vec2) -> GridCoords {
+        let cell = (position / self.grid_size as f32).floor();
+        GridCoords {
+            x: cell.x as i32,
+            y: cell.y as i32,
+        }
+    }
+
+    /// Level-local position of the center of `coords`.
+    pub fn cell_center(&self, coords: GridCoords) -> Vec2 {
+        (Vec2::new(coords.x as f32, coords.y as f32) + 0.5) * self.grid_size as f32
+    }
+}
+
+/// Wall cells of every spawned level with walls, by level entity.
+#[derive(Resource, Default)]
+pub struct LevelWalls {
+    pub levels: HashMap<Entity, WallGrid>,
+}
+
+/// The spawned level picked by `LevelSelection`, which enemies are spawned in.
+#[derive(SystemParam)]
+pub struct CurrentLevel<'w, 's> {
+    level_selection: Res<'w, LevelSelection>,
+    levels: Query<'w, 's, (Entity, &'static GlobalTransform, &'static LevelIid)>,
+    projects: Query<'w, 's, &'static Handle<LdtkProject>>,
+    project_assets: Res<'w, Assets<LdtkProject>>,
+}
+
+impl CurrentLevel<'_, '_> {
+    /// Entity, transform and LDtk definition of the level, `None` until it is spawned.
+    pub fn get(&self) -> Option<(Entity, &GlobalTransform, &bevy_ecs_ldtk::ldtk::Level)> {
+        let project = self.project_assets.get(self.projects.get_single().ok()?)?;
+
+        self.levels
+            .iter()
+            .find_map(|(level_entity, level_transform, level_iid)| {
+                let level = project.get_raw_level_by_iid(level_iid.get())?;
+
+                self.level_selection
+                    .is_match(
+                        &LevelIndices {
+                            level: 0,
+                            ..default()
+                        },
+                        level,
+                    )
+                    .then_some((level_entity, level_transform, level))
+            })
+    }
+}
+
 pub fn update_level_selection(
     level_query: Query<(&LevelIid, &Transform), Without<Player>>,
     player_query: Query<&GlobalTransform, With<Player>>,
@@ -101,7 +189,11 @@ pub fn camera_fit_inside_current_level(
 
     let player_translation = player_query.single().translation();
 
-    let (mut orthographic_projection, mut camera_transform, _) = camera_query.single_mut();
+    // there is no camera when running headless
+    let Ok((mut orthographic_projection, mut camera_transform, _)) = camera_query.get_single_mut()
+    else {
+        return;
+    };
 
     for (level_transform, level_iid) in &level_query {
         if let Some(ldtk_level) = project.get_raw_level_by_iid(level_iid.get()) {
@@ -158,6 +250,7 @@ pub fn spawn_wall_collision(
     level_query: Query<(Entity, &LevelIid)>,
     ldtk_projects: Query<&Handle<LdtkProject>>,
     ldtk_project_assets: Res<Assets<LdtkProject>>,
+    mut level_walls: ResMut<LevelWalls>,
 ) {
     // let project = project_assets.get(projects.single()).unwrap();
 
@@ -200,7 +293,7 @@ pub fn spawn_wall_collision(
 
     if !wall_query.is_empty() {
         level_query.for_each(|(level_entity, level_iid)| {
-            if let Some(level_walls) = level_to_wall_locations.get(&level_entity) {
+            if let Some(wall_locations) = level_to_wall_locations.get(&level_entity) {
                 let ldtk_project = ldtk_project_assets
                     .get(ldtk_projects.single())
                     .expect("Project should be loaded if level has spawned");
@@ -217,6 +310,16 @@ pub fn spawn_wall_collision(
                     ..
                 } = level.layer_instances()[0];
 
+                level_walls.levels.insert(
+                    level_entity,
+                    WallGrid {
+                        width,
+                        height,
+                        grid_size,
+                        walls: wall_locations.clone(),
+                    },
+                );
+
                 // combine wall tiles into flat "plates" in each individual row
                 let mut plate_stack: Vec<Vec<Plate>> = Vec::new();
 
@@ -226,7 +329,7 @@ pub fn spawn_wall_collision(
 
                     // + 1 to the width so the algorithm "terminates" plates that touch the right edge
                     for x in 0..width + 1 {
-                        match (plate_start, level_walls.contains(&GridCoords { x, y })) {
+                        match (plate_start, wall_locations.contains(&GridCoords { x, y })) {
                             (Some(s), false) => {
                                 row_plates.push(Plate {
                                     left: s,
@@ -288,6 +391,7 @@ pub fn spawn_wall_collision(
                                 ),
                                 // Sensor {},
                                 ActiveEvents::COLLISION_EVENTS,
+                                WallCollider,
                             ))
                             .insert(RigidBody::Fixed)
                             .insert(Friction::new(1.0))
@@ -339,14 +443,34 @@ pub fn fix_missing_ldtk_entities(
     mut commands: Commands,
     q_enemies: Query<(Entity, &Enemy), Without<Collider>>,
     q_items: Query<(Entity, &Item), Without<Collider>>,
+    archetypes: Res<EnemyArchetypeTable>,
+    mut rng: ResMut<GameRng>,
 ) {
     let asset_server = asset_server.into_inner();
     let texture_atlasses = texture_atlasses.into_inner();
 
     for (e, enemy) in q_enemies.iter().filter(|(_, m)| !m.is_dummy) {
-        let bundle = create_enemy_bundle(asset_server, texture_atlasses, false, enemy.enemy_type);
+        let Some(archetype) = archetypes.get(&enemy.archetype) else {
+            warn!("no enemy archetype {} for an LDtk entity", enemy.archetype);
+            commands.entity(e).despawn_recursive();
+            continue;
+        };
+
+        let bundle = create_enemy_bundle(
+            asset_server,
+            texture_atlasses,
+            false,
+            &enemy.archetype,
+            archetype,
+            &mut *rng,
+        );
         commands.entity(e).insert((
+            bundle.enemy,
+            bundle.spritesheet_bundle.texture_atlas,
+            bundle.spritesheet_bundle.sprite,
             bundle.collider_bundle,
+            bundle.active_events,
+            bundle.collision_groups,
             bundle.direction_update_time,
             bundle.animated_character_sprite,
             bundle.character_animation,
@@ -366,19 +490,25 @@ pub fn fix_missing_ldtk_entities(
 
 pub fn spawn_game_world(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    level_assets: Res<LevelAssets>,
     mut ew_level_change: EventWriter<LevelChangeEvent>,
 ) {
     commands.spawn(LdtkWorldBundle {
-        ldtk_handle: asset_server.load("levels/example.ldtk"),
+        ldtk_handle: level_assets.project.clone(),
         ..Default::default()
     });
 
     ew_level_change.send(LevelChangeEvent { level_id: 1 });
 }
 
-pub fn despawn_game_world(mut commands: Commands, level_query: Query<(Entity, &LevelSet)>) {
+pub fn despawn_game_world(
+    mut commands: Commands,
+    level_query: Query<(Entity, &LevelSet)>,
+    mut level_walls: ResMut<LevelWalls>,
+) {
     for (entity, _) in level_query.iter() {
         commands.entity(entity).despawn_recursive();
     }
+
+    level_walls.levels.clear();
 }
@@ -0,0 +1,281 @@
+use std::cmp::min;
+
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use rand::seq::SliceRandom;
+
+use crate::entities::player::Player;
+use crate::entities::weapons::weapon::{AcquireWeaponEvent, WeaponInventory};
+use crate::entities::weapons::weapon_table::WeaponTable;
+use crate::gameplay::experience::{Experience, Perk, Perks};
+use crate::loading::FontAssets;
+use crate::pause::{self, PauseState};
+use crate::replay::ReplayRecorder;
+use crate::rng::GameRng;
+use crate::ui::UIPlayerHealth;
+use crate::{ButtonColors, GameState};
+
+/// Number of perks offered on each level-up.
+const PERK_OFFER_COUNT: usize = 3;
+
+pub struct LevelUpPlugin;
+
+impl Plugin for LevelUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PerkOffers>()
+            .add_event::<ChoosePerkEvent>()
+            .add_systems(
+                Update,
+                start_level_up
+                    .run_if(in_state(GameState::GamePlay))
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(
+                OnEnter(PauseState::LevelUp),
+                (pause::freeze_game, roll_perk_offers, draw_level_up_menu).chain(),
+            )
+            .add_systems(
+                Update,
+                (click_perk_button, event_choose_perk)
+                    .chain()
+                    .run_if(in_state(PauseState::LevelUp)),
+            )
+            .add_systems(
+                OnExit(PauseState::LevelUp),
+                (pause::unfreeze_game, despawn_level_up_menu),
+            );
+    }
+}
+
+// ---------
+// Resources
+// ---------
+
+/// Perks offered on the level-up screen currently shown.
+#[derive(Resource, Default)]
+pub struct PerkOffers(pub Vec<Perk>);
+
+// ------
+// Events
+// ------
+
+/// Takes `perk` and closes the level-up screen, sent by its buttons, replays
+/// and the headless bot.
+#[derive(Event, Clone, Copy)]
+pub struct ChoosePerkEvent {
+    pub perk: Perk,
+}
+
+// ----------
+// Components
+// ----------
+
+#[derive(Component)]
+struct LevelUpMenu;
+
+#[derive(Component, Clone, Copy)]
+struct PerkButton(Perk);
+
+// -------
+// Systems
+// -------
+
+fn start_level_up(
+    experience: Res<Experience>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    if experience.pending_level_ups > 0 {
+        next_pause_state.set(PauseState::LevelUp);
+    }
+}
+
+fn roll_perk_offers(
+    mut perk_offers: ResMut<PerkOffers>,
+    mut rng: ResMut<GameRng>,
+    weapon_table: Res<WeaponTable>,
+    q_inventory: Query<&WeaponInventory, With<Player>>,
+) {
+    let available: Vec<Perk> = Perk::ALL
+        .into_iter()
+        .filter(|perk| match perk {
+            Perk::Weapon(kind) => q_inventory
+                .iter()
+                .all(|inventory| inventory.can_acquire(*kind, &weapon_table)),
+            _ => true,
+        })
+        .collect();
+
+    perk_offers.0 = available
+        .choose_multiple(&mut *rng, PERK_OFFER_COUNT)
+        .copied()
+        .collect();
+}
+
+fn perk_description(
+    perk: Perk,
+    inventory: Option<&WeaponInventory>,
+    weapon_table: &WeaponTable,
+) -> String {
+    match (perk, inventory) {
+        (Perk::Weapon(kind), Some(inventory)) => match inventory.level(kind) {
+            0 => "new weapon".to_string(),
+            level => format!(
+                "level {} -> {} (max {})",
+                level,
+                level + 1,
+                weapon_table.max_level(kind)
+            ),
+        },
+        _ => perk.description().to_string(),
+    }
+}
+
+fn draw_level_up_menu(
+    mut commands: Commands,
+    font_assets: Res<FontAssets>,
+    experience: Res<Experience>,
+    perk_offers: Res<PerkOffers>,
+    weapon_table: Res<WeaponTable>,
+    q_inventory: Query<&WeaponInventory, With<Player>>,
+) {
+    let inventory = q_inventory.get_single().ok();
+
+    let text_style = |font_size: f32| TextStyle {
+        font: font_assets.pixeloid_mono.clone(),
+        font_size,
+        color: Color::WHITE,
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(50),
+                ..default()
+            },
+            LevelUpMenu,
+            Name::new("level up menu"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("LEVEL UP!", text_style(80.0)));
+            parent.spawn(TextBundle::from_section(
+                format!("LV {}", experience.level - experience.pending_level_ups + 1),
+                text_style(30.0),
+            ));
+
+            for perk in perk_offers.0.iter() {
+                parent
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(400.0),
+                                height: Val::Px(90.0),
+                                flex_direction: FlexDirection::Column,
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            background_color: Color::rgba(1.0, 1.0, 1.0, 0.1).into(),
+                            ..default()
+                        },
+                        ButtonColors {
+                            normal: Color::WHITE,
+                            hovered: Color::YELLOW,
+                        },
+                        PerkButton(*perk),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(perk.name(), text_style(40.0)));
+                        parent.spawn(TextBundle::from_section(
+                            perk_description(*perk, inventory, &weapon_table),
+                            text_style(20.0),
+                        ));
+                    });
+            }
+        });
+}
+
+fn click_perk_button(
+    mut interaction_query: Query<
+        (&Interaction, &ButtonColors, &PerkButton, &Children),
+        Changed<Interaction>,
+    >,
+    mut q_text: Query<&mut Text>,
+    mut ev_choose_perk: EventWriter<ChoosePerkEvent>,
+) {
+    for (interaction, button_colors, perk_button, children) in &mut interaction_query {
+        let color = match *interaction {
+            Interaction::Pressed => {
+                ev_choose_perk.send(ChoosePerkEvent {
+                    perk: perk_button.0,
+                });
+                continue;
+            }
+            Interaction::Hovered => button_colors.hovered,
+            Interaction::None => button_colors.normal,
+        };
+
+        let mut texts = q_text.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+/// Applies the chosen perk. Several level-ups in a row show the screen again
+/// once play has resumed for a frame.
+pub fn event_choose_perk(
+    mut ev_choose_perk: EventReader<ChoosePerkEvent>,
+    mut perks: ResMut<Perks>,
+    mut experience: ResMut<Experience>,
+    mut q_player: Query<&mut Player>,
+    mut q_ui_healthbar: Query<&mut Style, With<UIPlayerHealth>>,
+    recorder: Option<ResMut<ReplayRecorder>>,
+    mut ev_acquire_weapon: EventWriter<AcquireWeaponEvent>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    // only one choice per level-up, even if a click and a replay both send one
+    let Some(perk) = ev_choose_perk.read().last().map(|ev| ev.perk) else {
+        return;
+    };
+
+    perks.add(perk);
+
+    match perk {
+        Perk::Heal => {
+            for mut player in q_player.iter_mut() {
+                player.health = min(player.health + 30, 100);
+
+                for mut style in q_ui_healthbar.iter_mut() {
+                    style.width = Val::Percent(player.health as f32);
+                }
+            }
+        }
+        Perk::Weapon(kind) => ev_acquire_weapon.send(AcquireWeaponEvent { kind }),
+        _ => {}
+    }
+
+    if let Some(mut recorder) = recorder {
+        recorder.replay.perks.push(perk);
+    }
+
+    experience.pending_level_ups = experience.pending_level_ups.saturating_sub(1);
+    next_pause_state.set(PauseState::Running);
+}
+
+fn despawn_level_up_menu(mut commands: Commands, q_menu: Query<Entity, With<LevelUpMenu>>) {
+    for entity in q_menu.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
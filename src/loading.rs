@@ -1,8 +1,17 @@
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
+use bevy_ecs_ldtk::prelude::LdtkProject;
 use bevy_kira_audio::AudioSource;
 
-use crate::{sprites::*, GameState};
+use crate::{
+    entities::{
+        characters::enemy_archetype::EnemyArchetypeTable,
+        weapons::{enemy_attack::EnemyAttackTable, weapon_table::WeaponTable},
+    },
+    gameplay::level_waves::LevelWaves,
+    sprites::*,
+    GameState,
+};
 
 pub struct LoadingPlugin;
 
@@ -19,6 +28,10 @@ impl Plugin for LoadingPlugin {
         app.add_collection_to_loading_state::<_, SceneAssets>(GameState::Loading);
         app.add_collection_to_loading_state::<_, AnimationAssets>(GameState::Loading);
         app.add_collection_to_loading_state::<_, StaticSpriteAssets>(GameState::Loading);
+        app.add_collection_to_loading_state::<_, WaveAssets>(GameState::Loading);
+        app.add_collection_to_loading_state::<_, WeaponAssets>(GameState::Loading);
+        app.add_collection_to_loading_state::<_, EnemyAssets>(GameState::Loading);
+        app.add_collection_to_loading_state::<_, LevelAssets>(GameState::Loading);
 
         app.init_resource::<FontAssets>();
         app.init_resource::<MaterialAssets>();
@@ -93,6 +106,34 @@ pub struct StaticSpriteAssets {
     pub pill: Handle<Image>,
 }
 
+#[derive(AssetCollection, Resource)]
+pub struct LevelAssets {
+    // loaded upfront so that level spawning takes the same frames in every run
+    #[asset(path = "levels/example.ldtk")]
+    pub project: Handle<LdtkProject>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct WaveAssets {
+    // every wave script in the folder, one per LDtk level, matched by `LevelID`
+    #[asset(path = "waves", collection(typed))]
+    pub levels: Vec<Handle<LevelWaves>>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct WeaponAssets {
+    #[asset(path = "weapons/player.weapons.ron")]
+    pub table: Handle<WeaponTable>,
+    #[asset(path = "weapons/enemy.attacks.ron")]
+    pub enemy_attacks: Handle<EnemyAttackTable>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct EnemyAssets {
+    #[asset(path = "enemies/enemy.archetypes.ron")]
+    pub archetypes: Handle<EnemyArchetypeTable>,
+}
+
 #[derive(AssetCollection, Resource)]
 pub struct AvatarAssets {
     #[asset(path = "avatars/alextime.png")]
@@ -23,19 +23,29 @@ use menu::*;
 use postprocessing::PostProcessSettings;
 
 mod audio;
+mod cli;
 mod controls;
+mod controls_menu;
 mod cutscene;
 mod entities;
 mod gameover;
 mod gameplay;
+mod headless;
+mod highscores;
 mod ldtk;
+mod levelup;
 mod loading;
 mod menu;
 mod particles;
+mod pause;
 mod physics;
 mod postprocessing;
+mod replay;
+mod rng;
+mod savegame;
 mod splashscreen;
 mod sprites;
+mod storage;
 mod ui;
 mod utils;
 
@@ -47,15 +57,26 @@ enum GameState {
     Menu,
     Cutscene,
     GamePlay,
+    /// Passed through to restart `GamePlay` from the pause menu.
+    Restarting,
     GameOver,
 }
 
 fn main() {
+    let args = cli::CommandLineArgs::parse();
+    let headless = args.headless;
+
     let mut app = App::new();
 
-    app.add_state::<GameState>()
+    app.insert_resource(rng::GameRng::new(args.seed))
+        .insert_resource(args)
+        .add_state::<GameState>();
+
+    if headless {
+        app.add_plugins((headless::headless_plugins(), headless::HeadlessPlugin));
+    } else {
         // .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()).set())
-        .add_plugins((
+        app.add_plugins((
             DefaultPlugins
                 .set(WindowPlugin {
                     primary_window: Some(Window {
@@ -74,20 +95,18 @@ fn main() {
                     filter: "info,wgpu_core=warn,wgpu_hal=warn,legend_of_mierda=debug,bevy_animation=error,bevy_gltf=error".into(),
                     level: bevy::log::Level::DEBUG,
                 }),
-            AudioPlugin, /*PostProcessPlugin*/))
-        .add_plugins((HookPlugin, PecsPlugin, TweeningPlugin, BevyMagicLight2DPlugin))
-        .add_plugins((LoadingPlugin, MenuPlugin, CutscenePlugin, LegendOfMierdaPlugin))
-        .add_plugins(audio::InternalAudioPlugin)
+            /*PostProcessPlugin*/))
+        .add_plugins(BevyMagicLight2DPlugin)
+        .add_plugins((MenuPlugin, CutscenePlugin))
+        .add_plugins((
+            controls_menu::ControlsMenuPlugin,
+            highscores::HighscoresPlugin,
+            savegame::SavegamePlugin,
+        ))
+        .add_plugins(replay::ReplayPlugin)
         .add_plugins(
-            WorldInspectorPlugin::default().run_if(input_toggle_active(false, KeyCode::Escape)),
+            WorldInspectorPlugin::default().run_if(input_toggle_active(false, KeyCode::F1)),
         )
-        .add_plugins(ParticleSystemPlugin)
-        // Physics
-        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
-        .insert_resource(RapierConfiguration {
-            gravity: Vec2::new(0.0, 0.0),
-            ..Default::default()
-        })
         // Magic Light
         .insert_resource(BevyMagicLight2DSettings {
             light_pass_params: LightPassParams {
@@ -99,6 +118,19 @@ fn main() {
             },
             ..default()
         })
+        .add_systems(Startup, (spawn_camera).after(setup_post_processing_camera));
+    }
+
+    app.add_plugins((AudioPlugin, HookPlugin, PecsPlugin, TweeningPlugin))
+        .add_plugins((LoadingPlugin, LegendOfMierdaPlugin))
+        .add_plugins(audio::InternalAudioPlugin)
+        .add_plugins(ParticleSystemPlugin)
+        // Physics
+        .add_plugins(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0))
+        .insert_resource(RapierConfiguration {
+            gravity: Vec2::new(0.0, 0.0),
+            ..Default::default()
+        })
         // LDTK
         .add_plugins(LdtkPlugin)
         .insert_resource(LdtkSettings {
@@ -148,11 +180,26 @@ impl Plugin for LegendOfMierdaPlugin {
             gameplay::GameplayPlugin,
             gameover::GameOverPlugin,
             splashscreen::SplashscreenPlugin,
+            pause::PausePlugin,
+            levelup::LevelUpPlugin,
         ))
-        .add_systems(Startup, (spawn_camera).after(setup_post_processing_camera))
         .add_systems(
             OnEnter(GameState::GamePlay),
-            (ldtk::spawn_game_world, ui::draw_ui),
+            (rng::reseed_game_rng, ldtk::spawn_game_world, ui::draw_ui).chain(),
+        )
+        .configure_sets(
+            Update,
+            (
+                rng::GameRngSet::LevelChange,
+                rng::GameRngSet::HotReloadWaves,
+                rng::GameRngSet::RestoreRun,
+                rng::GameRngSet::WaveTimers,
+                rng::GameRngSet::SpawnEnemies,
+                rng::GameRngSet::SpawnItems,
+                rng::GameRngSet::LdtkEnemies,
+                rng::GameRngSet::AimSpeargun,
+            )
+                .chain(),
         )
         .add_systems(
             OnExit(GameState::GamePlay),
@@ -169,7 +216,10 @@ impl Plugin for LegendOfMierdaPlugin {
         ) // Housekeeping
         .add_systems(
             Update,
-            (ldtk::hide_dummy_entities, ldtk::fix_missing_ldtk_entities)
+            (
+                ldtk::hide_dummy_entities,
+                ldtk::fix_missing_ldtk_entities.in_set(rng::GameRngSet::LdtkEnemies),
+            )
                 .run_if(in_state(GameState::GamePlay)),
         )
         // Sprites
@@ -179,11 +229,20 @@ impl Plugin for LegendOfMierdaPlugin {
                 .run_if(in_state(GameState::GamePlay)),
         )
         // Controls
+        .insert_resource(controls::InputMap::load())
         .add_event::<controls::ControlEvent>()
         .add_systems(
             Update,
-            (controls::keyboard_controls, controls::control_character)
-                .run_if(in_state(GameState::GamePlay)),
+            (
+                controls::input_controls.run_if(
+                    not(resource_exists::<replay::ReplayPlayer>())
+                        .and_then(not(resource_exists::<headless::HeadlessRun>())),
+                ),
+                replay::replay_controls.run_if(resource_exists::<replay::ReplayPlayer>()),
+                controls::control_character,
+            )
+                .run_if(in_state(GameState::GamePlay))
+                .run_if(in_state(pause::PauseState::Running)),
         )
         // Particles
         .add_systems(
@@ -191,6 +250,7 @@ impl Plugin for LegendOfMierdaPlugin {
             (particles::fix_particle_transform_z).run_if(in_state(GameState::GamePlay)),
         )
         // App Events
-        .add_event::<ldtk::LevelChangeEvent>();
+        .add_event::<ldtk::LevelChangeEvent>()
+        .init_resource::<ldtk::LevelWalls>();
     }
 }
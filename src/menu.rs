@@ -1,4 +1,7 @@
+use crate::controls_menu::OpenControlsMenuButton;
+use crate::highscores::OpenHighscoresButton;
 use crate::loading::FontAssets;
+use crate::savegame::{ContinueRunButton, SavedRun};
 
 use crate::CutsceneAssets;
 use crate::GameState;
@@ -128,6 +131,89 @@ fn setup_menu(
                         StartGameButton,
                     ));
                 });
+
+            if SavedRun::load().is_some() {
+                children
+                    .spawn((
+                        ButtonBundle {
+                            style: Style {
+                                width: Val::Px(318.0),
+                                height: Val::Px(60.0),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..Default::default()
+                            },
+                            background_color: Color::rgba_u8(0, 0, 0, 0).into(),
+                            ..Default::default()
+                        },
+                        ButtonColors::default(),
+                        ContinueRunButton,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(
+                            "CONTINUE",
+                            TextStyle {
+                                font_size: 50.0,
+                                font: font_assets.pixeloid_mono.clone(),
+                                color: Color::WHITE,
+                            },
+                        ));
+                    });
+            }
+
+            children
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(318.0),
+                            height: Val::Px(60.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: Color::rgba_u8(0, 0, 0, 0).into(),
+                        ..Default::default()
+                    },
+                    ButtonColors::default(),
+                    OpenControlsMenuButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "CONTROLS",
+                        TextStyle {
+                            font_size: 50.0,
+                            font: font_assets.pixeloid_mono.clone(),
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
+
+            children
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(318.0),
+                            height: Val::Px(60.0),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: Color::rgba_u8(0, 0, 0, 0).into(),
+                        ..Default::default()
+                    },
+                    ButtonColors::default(),
+                    OpenHighscoresButton,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "SCORES",
+                        TextStyle {
+                            font_size: 50.0,
+                            font: font_assets.pixeloid_mono.clone(),
+                            color: Color::WHITE,
+                        },
+                    ));
+                });
         });
 }
 
@@ -137,12 +223,12 @@ pub struct ChangeState(pub GameState);
 fn click_play_button(
     mut next_state: ResMut<NextState<GameState>>,
     mut interaction_query: Query<
-        (&Interaction, &ButtonColors, Option<&ChangeState>),
+        (&Interaction, &ButtonColors, Option<&ChangeState>, &Children),
         (Changed<Interaction>, With<Button>),
     >,
-    mut start_game_button: Query<(&mut Text, &StartGameButton)>,
+    mut q_text: Query<&mut Text>,
 ) {
-    for (interaction, button_colors, change_state) in &mut interaction_query {
+    for (interaction, button_colors, change_state, children) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
                 if let Some(state) = change_state {
@@ -150,12 +236,14 @@ fn click_play_button(
                 }
             }
             Interaction::Hovered => {
-                for (mut text, _) in start_game_button.iter_mut() {
+                let mut texts = q_text.iter_many_mut(children);
+                while let Some(mut text) = texts.fetch_next() {
                     text.sections[0].style.color = button_colors.hovered;
                 }
             }
             Interaction::None => {
-                for (mut text, _) in start_game_button.iter_mut() {
+                let mut texts = q_text.iter_many_mut(children);
+                while let Some(mut text) = texts.fetch_next() {
                     text.sections[0].style.color = button_colors.normal;
                 }
             }
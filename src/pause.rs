@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+use bevy::ui::FocusPolicy;
+use bevy_rapier2d::prelude::*;
+
+use crate::controls_menu::{self, OpenControlsMenuButton};
+use crate::loading::FontAssets;
+use crate::savegame::SaveRunButton;
+use crate::{ButtonColors, GameState};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_state::<PauseState>()
+            .add_systems(
+                Update,
+                toggle_pause
+                    .run_if(in_state(GameState::GamePlay))
+                    .run_if(not(controls_menu::is_open)),
+            )
+            .add_systems(
+                Update,
+                click_pause_button.run_if(in_state(PauseState::Paused)),
+            )
+            .add_systems(OnEnter(PauseState::Paused), (freeze_game, draw_pause_menu))
+            .add_systems(
+                OnExit(PauseState::Paused),
+                (unfreeze_game, despawn_pause_menu),
+            )
+            .add_systems(OnExit(GameState::GamePlay), resume)
+            .add_systems(OnEnter(GameState::Restarting), restart_gameplay);
+    }
+}
+
+/// Whether gameplay is running or frozen behind the pause menu or the level-up screen.
+/// Only meaningful in [`GameState::GamePlay`], which is kept while paused so
+/// that the game world stays spawned.
+#[derive(States, Default, Clone, Copy, Eq, PartialEq, Debug, Hash)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+    LevelUp,
+}
+
+// ----------
+// Components
+// ----------
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component, Clone, Copy)]
+enum PauseMenuButton {
+    Resume,
+    Restart,
+    Quit,
+}
+
+// -------
+// Systems
+// -------
+
+fn toggle_pause(
+    keys: Res<Input<KeyCode>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+) {
+    let pressed = keys.just_pressed(KeyCode::Escape)
+        || gamepad_buttons
+            .get_just_pressed()
+            .any(|button| button.button_type == GamepadButtonType::Start);
+
+    if !pressed {
+        return;
+    }
+
+    next_pause_state.set(match pause_state.get() {
+        PauseState::Running => PauseState::Paused,
+        PauseState::Paused => PauseState::Running,
+        // a perk has to be picked first
+        PauseState::LevelUp => return,
+    });
+}
+
+/// Virtual time drives the wave, weapon and promise timers, physics has to be stopped on its own.
+pub(crate) fn freeze_game(
+    mut time: ResMut<Time<Virtual>>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    time.pause();
+    rapier_config.physics_pipeline_active = false;
+}
+
+pub(crate) fn unfreeze_game(
+    mut time: ResMut<Time<Virtual>>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+) {
+    time.unpause();
+    rapier_config.physics_pipeline_active = true;
+}
+
+fn resume(mut next_pause_state: ResMut<NextState<PauseState>>) {
+    next_pause_state.set(PauseState::Running);
+}
+
+/// `GamePlay` can't transition to itself, restarting goes through `Restarting`
+/// so that the world is despawned and spawned again.
+fn restart_gameplay(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::GamePlay);
+}
+
+fn draw_pause_menu(mut commands: Commands, font_assets: Res<FontAssets>) {
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(10.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+                focus_policy: FocusPolicy::Block,
+                z_index: ZIndex::Global(50),
+                ..default()
+            },
+            PauseMenu,
+            Name::new("pause menu"),
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "PAUSA",
+                TextStyle {
+                    font: font_assets.pixeloid_mono.clone(),
+                    font_size: 100.0,
+                    color: Color::WHITE,
+                },
+            ));
+
+            spawn_button(parent, &font_assets, "RESUME", PauseMenuButton::Resume);
+            spawn_button(parent, &font_assets, "RESTART", PauseMenuButton::Restart);
+            spawn_button(parent, &font_assets, "OPTIONS", OpenControlsMenuButton);
+            spawn_button(parent, &font_assets, "SAVE & QUIT", SaveRunButton);
+            spawn_button(parent, &font_assets, "QUIT", PauseMenuButton::Quit);
+        });
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    font_assets: &FontAssets,
+    label: &str,
+    button: impl Component,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: Style {
+                    width: Val::Px(318.0),
+                    height: Val::Px(60.0),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba_u8(0, 0, 0, 0).into(),
+                ..default()
+            },
+            ButtonColors {
+                normal: Color::WHITE,
+                hovered: Color::YELLOW,
+            },
+            button,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                TextStyle {
+                    font: font_assets.pixeloid_mono.clone(),
+                    font_size: 50.0,
+                    color: Color::WHITE,
+                },
+            ));
+        });
+}
+
+/// Handles the pause menu buttons and colors every hovered button while paused,
+/// including the ones of the controls menu opened from OPTIONS.
+fn click_pause_button(
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut interaction_query: Query<
+        (
+            &Interaction,
+            &ButtonColors,
+            Option<&PauseMenuButton>,
+            &Children,
+        ),
+        (Changed<Interaction>, With<Button>),
+    >,
+    mut q_text: Query<&mut Text>,
+) {
+    for (interaction, button_colors, pause_menu_button, children) in &mut interaction_query {
+        let color = match *interaction {
+            Interaction::Pressed => {
+                match pause_menu_button {
+                    Some(PauseMenuButton::Resume) => next_pause_state.set(PauseState::Running),
+                    Some(PauseMenuButton::Restart) => next_state.set(GameState::Restarting),
+                    Some(PauseMenuButton::Quit) => next_state.set(GameState::Menu),
+                    None => {}
+                }
+                continue;
+            }
+            Interaction::Hovered => button_colors.hovered,
+            Interaction::None => button_colors.normal,
+        };
+
+        let mut texts = q_text.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].style.color = color;
+        }
+    }
+}
+
+fn despawn_pause_menu(mut commands: Commands, q_pause_menu: Query<Entity, With<PauseMenu>>) {
+    for entity in q_pause_menu.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
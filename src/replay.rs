@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::CommandLineArgs,
+    controls::ControlEvent,
+    entities::{characters::enemy::Enemy, player::Player},
+    gameplay::{experience::Perk, scoring::Score},
+    levelup::ChoosePerkEvent,
+    pause::PauseState,
+    rng::GameRng,
+    GameState,
+};
+
+/// Frame time used while recording or replaying, so that every frame of a
+/// replay advances timers and physics by exactly the same amount.
+pub const REPLAY_TIMESTEP: f32 = 1.0 / 60.0;
+
+/// Recorded frames between two checksums of the run state.
+const CHECKSUM_INTERVAL: usize = 60;
+
+/// A recorded run: the seed it was played with, the player input of every
+/// gameplay frame, starting from the first frame the player exists, the
+/// perks chosen on each level-up, and a checksum of the run state every
+/// `CHECKSUM_INTERVAL` frames.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct Replay {
+    pub seed: u64,
+    pub timestep: f32,
+    pub frames: Vec<ControlEvent>,
+    pub perks: Vec<Perk>,
+    pub checksums: Vec<RunChecksum>,
+}
+
+/// What a replay must reproduce exactly at each checksum, or it has drifted
+/// from the recorded run.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct RunChecksum {
+    pub score: u32,
+    pub player_position: Option<Vec2>,
+    pub enemies_alive: usize,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Replay, String> {
+        let contents = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        ron::from_str(&contents).map_err(|e| e.to_string())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let contents = ron::to_string(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, contents).map_err(|e| e.to_string())
+    }
+}
+
+// ---------
+// Resources
+// ---------
+
+#[derive(Resource)]
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    pub replay: Replay,
+}
+
+#[derive(Resource)]
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub frame: usize,
+    /// Index of the next perk to choose.
+    pub perk: usize,
+    /// Index of the next checksum to compare.
+    pub checksum: usize,
+}
+
+#[derive(SystemParam)]
+pub struct RunState<'w, 's> {
+    score: Res<'w, Score>,
+    q_player: Query<'w, 's, &'static Transform, With<Player>>,
+    q_enemies: Query<'w, 's, &'static Enemy>,
+}
+
+impl RunState<'_, '_> {
+    fn checksum(&self) -> RunChecksum {
+        RunChecksum {
+            score: self.score.score,
+            player_position: self
+                .q_player
+                .get_single()
+                .ok()
+                .map(|transform| transform.translation.truncate()),
+            enemies_alive: self
+                .q_enemies
+                .iter()
+                .filter(|enemy| !enemy.is_dummy && enemy.health > 0)
+                .count(),
+        }
+    }
+}
+
+// -------
+// Systems
+// -------
+
+fn start_recording(mut recorder: ResMut<ReplayRecorder>, rng: Res<GameRng>) {
+    recorder.replay = Replay {
+        seed: rng.seed,
+        timestep: REPLAY_TIMESTEP,
+        ..default()
+    };
+}
+
+fn record_checksum(mut recorder: ResMut<ReplayRecorder>, run_state: RunState) {
+    let replay = &mut recorder.replay;
+    if replay.frames.len() != (replay.checksums.len() + 1) * CHECKSUM_INTERVAL {
+        return;
+    }
+
+    replay.checksums.push(run_state.checksum());
+}
+
+fn save_recording(recorder: Res<ReplayRecorder>) {
+    match recorder.replay.save(&recorder.path) {
+        Ok(()) => info!(
+            "recorded {} frames to {}",
+            recorder.replay.frames.len(),
+            recorder.path.display()
+        ),
+        Err(e) => error!("could not save replay {}: {}", recorder.path.display(), e),
+    }
+}
+
+fn save_recording_on_exit(
+    mut ev_app_exit: EventReader<bevy::app::AppExit>,
+    recorder: Res<ReplayRecorder>,
+    state: Res<State<GameState>>,
+) {
+    if ev_app_exit.read().count() > 0 && *state.get() == GameState::GamePlay {
+        save_recording(recorder);
+    }
+}
+
+pub fn replay_controls(
+    mut player: ResMut<ReplayPlayer>,
+    q_player: Query<(), With<Player>>,
+    mut ev_control: EventWriter<ControlEvent>,
+) {
+    // frames are only recorded once the player is spawned
+    if q_player.is_empty() {
+        return;
+    }
+
+    let frame = player.frame;
+    player.frame += 1;
+
+    match player.replay.frames.get(frame) {
+        Some(control) => ev_control.send(*control),
+        None if frame == player.replay.frames.len() => info!("replay finished"),
+        None => {}
+    }
+}
+
+/// Stops the replay as soon as the run state differs from the recording, a
+/// drifting replay would otherwise report a run that never happened.
+fn check_checksum(mut player: ResMut<ReplayPlayer>, run_state: RunState) {
+    if player.frame != (player.checksum + 1) * CHECKSUM_INTERVAL {
+        return;
+    }
+
+    let Some(expected) = player.replay.checksums.get(player.checksum).copied() else {
+        return;
+    };
+    player.checksum += 1;
+
+    let actual = run_state.checksum();
+    if actual != expected {
+        panic!(
+            "replay diverged from the recorded run at frame {}: expected {:?}, got {:?}",
+            player.frame, expected, actual
+        );
+    }
+}
+
+fn replay_perk_choice(
+    mut player: ResMut<ReplayPlayer>,
+    mut ev_choose_perk: EventWriter<ChoosePerkEvent>,
+) {
+    let Some(perk) = player.replay.perks.get(player.perk).copied() else {
+        warn!("replay has no perk for this level-up, pick one to continue");
+        return;
+    };
+
+    player.perk += 1;
+    ev_choose_perk.send(ChoosePerkEvent { perk });
+}
+
+pub(crate) fn skip_to_gameplay(mut next_state: ResMut<NextState<GameState>>) {
+    next_state.set(GameState::GamePlay);
+}
+
+fn rewind_replay(mut player: ResMut<ReplayPlayer>) {
+    player.frame = 0;
+    player.perk = 0;
+    player.checksum = 0;
+}
+
+pub(crate) fn use_fixed_physics_timestep(
+    mut rapier_config: ResMut<RapierConfiguration>,
+    time_update_strategy: Res<TimeUpdateStrategy>,
+) {
+    if let TimeUpdateStrategy::ManualDuration(duration) = *time_update_strategy {
+        rapier_config.timestep_mode = TimestepMode::Fixed {
+            dt: duration.as_secs_f32(),
+            substeps: 1,
+        };
+    }
+}
+
+// ------
+// Plugin
+// ------
+
+/// Records runs with `--record <file>` and plays them back with `--replay <file>`.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        let args = app.world.resource::<CommandLineArgs>().clone();
+
+        if let Some(path) = args.replay {
+            let replay = match Replay::load(&path) {
+                Ok(replay) => replay,
+                Err(e) => {
+                    error!("could not load replay {}: {}", path.display(), e);
+                    return;
+                }
+            };
+
+            app.insert_resource(GameRng::new(Some(replay.seed)))
+                .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                    replay.timestep,
+                )))
+                .insert_resource(ReplayPlayer {
+                    replay,
+                    frame: 0,
+                    perk: 0,
+                    checksum: 0,
+                })
+                .add_systems(Startup, use_fixed_physics_timestep)
+                .add_systems(OnEnter(GameState::Splash), skip_to_gameplay)
+                .add_systems(OnEnter(GameState::GamePlay), rewind_replay)
+                .add_systems(OnEnter(PauseState::LevelUp), replay_perk_choice)
+                .add_systems(
+                    PostUpdate,
+                    check_checksum
+                        .after(PhysicsSet::Writeback)
+                        .run_if(in_state(GameState::GamePlay))
+                        .run_if(in_state(PauseState::Running)),
+                );
+        } else if let Some(path) = args.record {
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                REPLAY_TIMESTEP,
+            )))
+            .insert_resource(ReplayRecorder {
+                path,
+                replay: Replay::default(),
+            })
+            .add_systems(Startup, use_fixed_physics_timestep)
+            .add_systems(
+                OnEnter(GameState::GamePlay),
+                start_recording.after(crate::rng::reseed_game_rng),
+            )
+            .add_systems(
+                PostUpdate,
+                record_checksum
+                    .after(PhysicsSet::Writeback)
+                    .run_if(in_state(GameState::GamePlay))
+                    .run_if(in_state(PauseState::Running)),
+            )
+            .add_systems(OnExit(GameState::GamePlay), save_recording)
+            .add_systems(Last, save_recording_on_exit);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+/// Single source of randomness for gameplay. Every run is reseeded on entering
+/// [`crate::GameState::GamePlay`], so a seed reproduces the run.
+#[derive(Resource)]
+pub struct GameRng {
+    /// Seed of the current run.
+    pub seed: u64,
+    /// Seed requested on the command line, reused for every run when set.
+    pub fixed_seed: Option<u64>,
+    rng: StdRng,
+}
+
+impl GameRng {
+    pub fn new(fixed_seed: Option<u64>) -> Self {
+        let seed = fixed_seed.unwrap_or_else(rand::random);
+
+        GameRng {
+            seed,
+            fixed_seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    pub fn reseed(&mut self) {
+        *self = GameRng::new(self.fixed_seed);
+    }
+}
+
+impl RngCore for GameRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+/// Every `Update` system drawing from [`GameRng`], one set per system, chained
+/// in this order by [`crate::LegendOfMierdaPlugin`]. Unordered systems may run
+/// in any order, which would hand them different numbers from run to run.
+#[derive(SystemSet, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum GameRngSet {
+    LevelChange,
+    HotReloadWaves,
+    RestoreRun,
+    WaveTimers,
+    SpawnEnemies,
+    SpawnItems,
+    LdtkEnemies,
+    AimSpeargun,
+}
+
+pub fn reseed_game_rng(mut rng: ResMut<GameRng>) {
+    rng.reseed();
+    info!("run seed: {}", rng.seed);
+}
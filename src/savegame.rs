@@ -0,0 +1,411 @@
+use std::time::Duration;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::entities::characters::enemy::{spawn_enemy, Enemy, EnemySpawner, SpawnEnemyEvent};
+use crate::entities::characters::enemy_archetype::EnemyArchetypeId;
+use crate::entities::items::biboran::{ActivateBiboranEvent, BiboranTimer};
+use crate::entities::items::item::{spawn_item_from_dummy, Item, ItemType, SpawnItemEvent};
+use crate::entities::items::xp_gem::{SpawnXpGemEvent, XpGem};
+use crate::entities::player::Player;
+use crate::entities::weapons::weapon::{Weapon, WeaponInventory, WeaponKind};
+use crate::gameplay::experience::{Experience, Perks};
+use crate::gameplay::scoring::{RunStats, Score};
+use crate::gameplay::waves::{GameplayState, WaveEntry, WaveEvent};
+use crate::ldtk::CurrentLevel;
+use crate::pause::PauseState;
+use crate::rng::GameRngSet;
+use crate::ui::UIPlayerHealth;
+use crate::{storage, GameState};
+
+const SAVED_RUN_FILE: &str = "run.ron";
+
+pub struct SavegamePlugin;
+
+impl Plugin for SavegamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            click_save_run_button.run_if(in_state(PauseState::Paused)),
+        )
+        .add_systems(
+            Update,
+            click_continue_run_button.run_if(in_state(GameState::Menu)),
+        )
+        .add_systems(
+            Update,
+            restore_run
+                .in_set(GameRngSet::RestoreRun)
+                .run_if(in_state(GameState::GamePlay))
+                .run_if(resource_exists::<PendingRestore>()),
+        );
+    }
+}
+
+// ----
+// Data
+// ----
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedEnemy {
+    pub archetype: EnemyArchetypeId,
+    pub health: u16,
+    pub transform: Transform,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedItem {
+    pub item_type: ItemType,
+    pub transform: Transform,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedXpGem {
+    pub xp: u32,
+    pub transform: Transform,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedWeapon {
+    pub kind: WeaponKind,
+    pub level: u32,
+}
+
+/// Snapshot of a run in progress, saved to `run.ron` in the data directory
+/// from the pause menu. Resuming it from the main menu deletes the file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SavedRun {
+    pub level_id: usize,
+    pub wave_number: usize,
+    pub event_queue: Vec<WaveEntry>,
+    pub wave_elapsed: Duration,
+    pub wave_event_elapsed: Duration,
+    pub player_health: u16,
+    pub player_transform: Transform,
+    pub enemies: Vec<SavedEnemy>,
+    pub items: Vec<SavedItem>,
+    pub xp_gems: Vec<SavedXpGem>,
+    /// Time left of the biboran effect, if one is active.
+    pub biboran_remaining: Option<Duration>,
+    pub score: u32,
+    pub time_survived: Duration,
+    pub damage_taken: u32,
+    pub enemies_killed: HashMap<EnemyArchetypeId, u32>,
+    pub experience: Experience,
+    pub perks: Perks,
+    pub weapons: Vec<SavedWeapon>,
+}
+
+impl SavedRun {
+    pub fn load() -> Option<Self> {
+        storage::load(SAVED_RUN_FILE)
+    }
+
+    pub fn save(&self) {
+        storage::save(SAVED_RUN_FILE, self);
+    }
+
+    pub fn remove() {
+        storage::remove(SAVED_RUN_FILE);
+    }
+}
+
+/// Run loaded from disk, applied once the game world has been spawned.
+#[derive(Resource)]
+struct PendingRestore(SavedRun);
+
+/// Events of the fresh run started by the level change, dropped when a saved
+/// run replaces it.
+#[derive(SystemParam)]
+struct FreshRunEvents<'w> {
+    wave: ResMut<'w, Events<WaveEvent>>,
+    spawn_enemy: ResMut<'w, Events<SpawnEnemyEvent>>,
+    spawn_item: ResMut<'w, Events<SpawnItemEvent>>,
+}
+
+impl<'w> FreshRunEvents<'w> {
+    fn clear(&mut self) {
+        self.wave.clear();
+        self.spawn_enemy.clear();
+        self.spawn_item.clear();
+    }
+}
+
+// ----------
+// Components
+// ----------
+
+/// Put on a button to save the current run and quit to the main menu.
+#[derive(Component)]
+pub struct SaveRunButton;
+
+/// Put on a button to resume the saved run.
+#[derive(Component)]
+pub struct ContinueRunButton;
+
+// -------
+// Systems
+// -------
+
+fn click_save_run_button(
+    q_button: Query<&Interaction, (Changed<Interaction>, With<SaveRunButton>)>,
+    q_player: Query<(&Player, &Transform, &WeaponInventory)>,
+    q_enemies: Query<(&Enemy, &Transform)>,
+    q_items: Query<(&Item, &Transform)>,
+    q_xp_gems: Query<(&XpGem, &Transform)>,
+    gameplay_state: Res<GameplayState>,
+    score: Res<Score>,
+    run_stats: Res<RunStats>,
+    experience: Res<Experience>,
+    perks: Res<Perks>,
+    biboran_timer: Res<BiboranTimer>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !q_button.iter().any(|i| *i == Interaction::Pressed) {
+        return;
+    }
+
+    let (Ok((player, player_transform, inventory)), Some(level_id)) =
+        (q_player.get_single(), gameplay_state.current_level_id)
+    else {
+        warn!("nothing to save, the run hasn't started yet");
+        return;
+    };
+
+    let biboran_remaining = biboran_timer.0.remaining();
+    let biboran_remaining = if biboran_timer.0.finished() || biboran_remaining.is_zero() {
+        None
+    } else {
+        Some(biboran_remaining)
+    };
+
+    let saved_run = SavedRun {
+        level_id,
+        wave_number: gameplay_state.wave_number.unwrap_or(0),
+        event_queue: gameplay_state.event_queue.clone(),
+        wave_elapsed: gameplay_state.wave_timer.elapsed(),
+        wave_event_elapsed: gameplay_state.wave_event_timer.elapsed(),
+        player_health: player.health,
+        player_transform: *player_transform,
+        enemies: q_enemies
+            .iter()
+            .filter(|(enemy, _)| !enemy.is_dummy && enemy.health > 0)
+            .map(|(enemy, transform)| SavedEnemy {
+                archetype: enemy.archetype.clone(),
+                health: enemy.health,
+                transform: *transform,
+            })
+            .collect(),
+        items: q_items
+            .iter()
+            .filter(|(item, _)| !item.is_dummy && item.item_type != ItemType::XpGem)
+            .map(|(item, transform)| SavedItem {
+                item_type: item.item_type,
+                transform: *transform,
+            })
+            .collect(),
+        xp_gems: q_xp_gems
+            .iter()
+            .map(|(xp_gem, transform)| SavedXpGem {
+                xp: xp_gem.xp,
+                transform: *transform,
+            })
+            .collect(),
+        biboran_remaining,
+        score: score.score,
+        time_survived: run_stats.time_survived,
+        damage_taken: run_stats.damage_taken,
+        enemies_killed: run_stats.enemies_killed.clone(),
+        experience: experience.clone(),
+        perks: perks.clone(),
+        weapons: inventory
+            .weapons
+            .iter()
+            .map(|weapon| SavedWeapon {
+                kind: weapon.kind,
+                level: weapon.level,
+            })
+            .collect(),
+    };
+
+    saved_run.save();
+    info!(
+        "saved run at wave {} with {} enemies",
+        saved_run.wave_number + 1,
+        saved_run.enemies.len()
+    );
+
+    next_state.set(GameState::Menu);
+}
+
+fn click_continue_run_button(
+    mut commands: Commands,
+    q_button: Query<&Interaction, (Changed<Interaction>, With<ContinueRunButton>)>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if !q_button.iter().any(|i| *i == Interaction::Pressed) {
+        return;
+    }
+
+    let Some(saved_run) = SavedRun::load() else {
+        warn!("the saved run could not be loaded");
+        return;
+    };
+
+    // the file is only removed once the run is restored, see `restore_run`
+    commands.insert_resource(PendingRestore(saved_run));
+    next_state.set(GameState::GamePlay);
+}
+
+/// Waits for the player to be spawned, then replaces the fresh run started by
+/// the level change with the saved one. Gives up, keeping the file, if there
+/// is no level to restore it in.
+fn restore_run(
+    mut commands: Commands,
+    pending_restore: Res<PendingRestore>,
+    mut q_player: Query<(&mut Player, &mut Transform, &mut WeaponInventory)>,
+    q_enemies: Query<(Entity, &Enemy)>,
+    q_items: Query<(Entity, &Parent, &Item)>,
+    current_level: CurrentLevel,
+    mut q_ui_healthbar: Query<&mut Style, With<UIPlayerHealth>>,
+    mut gameplay_state: ResMut<GameplayState>,
+    mut score: ResMut<Score>,
+    mut run_stats: ResMut<RunStats>,
+    mut experience: ResMut<Experience>,
+    mut perks: ResMut<Perks>,
+    mut fresh_run_events: FreshRunEvents,
+    mut enemy_spawner: EnemySpawner,
+    mut ev_activate_biboran: EventWriter<ActivateBiboranEvent>,
+    mut ev_spawn_xp_gem: EventWriter<SpawnXpGemEvent>,
+) {
+    let saved_run = &pending_restore.0;
+
+    let Ok((mut player, mut player_transform, mut inventory)) = q_player.get_single_mut() else {
+        return;
+    };
+
+    if gameplay_state.current_level_id.is_none() {
+        return;
+    }
+
+    // the player is an LDtk entity, its level is spawned by now
+    let Some((level, _, _)) = current_level.get() else {
+        warn!("no level to resume the saved run in");
+        commands.remove_resource::<PendingRestore>();
+        return;
+    };
+
+    if gameplay_state.current_level_id != Some(saved_run.level_id) {
+        warn!(
+            "saved run is from level {}, resuming it in level {:?}",
+            saved_run.level_id, gameplay_state.current_level_id
+        );
+    }
+
+    // waves
+    match gameplay_state.waves.get(saved_run.wave_number).cloned() {
+        Some(wave) => {
+            gameplay_state.wave_number = Some(saved_run.wave_number);
+            gameplay_state.event_queue = saved_run.event_queue.clone();
+            gameplay_state.wave_timer = Timer::new(wave.wave_duration, TimerMode::Once);
+            gameplay_state
+                .wave_timer
+                .set_elapsed(saved_run.wave_elapsed);
+            gameplay_state.wave_event_timer = Timer::new(wave.event_duration, TimerMode::Once);
+            gameplay_state
+                .wave_event_timer
+                .set_elapsed(saved_run.wave_event_elapsed);
+        }
+        None => warn!(
+            "saved wave {} doesn't exist anymore, starting from the first wave",
+            saved_run.wave_number + 1
+        ),
+    }
+
+    // drop what the fresh run already spawned or is about to spawn
+    fresh_run_events.clear();
+
+    for (entity, enemy) in q_enemies.iter() {
+        if !enemy.is_dummy {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for (entity, _, item) in q_items.iter() {
+        if !item.is_dummy {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    // player
+    player.health = saved_run.player_health;
+    *player_transform = saved_run.player_transform;
+    for mut style in q_ui_healthbar.iter_mut() {
+        style.width = Val::Percent(player.health as f32);
+    }
+
+    // enemies and items
+    for saved_enemy in &saved_run.enemies {
+        let Some(mut enemy_bundle) = enemy_spawner.bundle(&saved_enemy.archetype) else {
+            warn!("no enemy archetype {} to restore", saved_enemy.archetype);
+            continue;
+        };
+        enemy_bundle.enemy.health = saved_enemy.health;
+
+        spawn_enemy(&mut commands, level, enemy_bundle, saved_enemy.transform);
+    }
+
+    for saved_item in &saved_run.items {
+        let dummy = q_items
+            .iter()
+            .find(|(_, _, item)| item.is_dummy && item.item_type == saved_item.item_type);
+
+        let Some((dummy, level, _)) = dummy else {
+            warn!("no dummy to restore a {:?} from", saved_item.item_type);
+            continue;
+        };
+
+        spawn_item_from_dummy(
+            &mut commands,
+            dummy,
+            level.get(),
+            saved_item.item_type,
+            saved_item.transform,
+        );
+    }
+
+    for saved_xp_gem in &saved_run.xp_gems {
+        ev_spawn_xp_gem.send(SpawnXpGemEvent {
+            level,
+            transform: saved_xp_gem.transform,
+            xp: saved_xp_gem.xp,
+        });
+    }
+
+    if let Some(duration) = saved_run.biboran_remaining {
+        ev_activate_biboran.send(ActivateBiboranEvent { duration });
+    }
+
+    // score
+    score.score = saved_run.score;
+    *run_stats = RunStats {
+        time_survived: saved_run.time_survived,
+        damage_taken: saved_run.damage_taken,
+        enemies_killed: saved_run.enemies_killed.clone(),
+    };
+    *experience = saved_run.experience.clone();
+    *perks = saved_run.perks.clone();
+
+    inventory.weapons = saved_run
+        .weapons
+        .iter()
+        .map(|weapon| Weapon::new(weapon.kind, weapon.level))
+        .collect();
+
+    info!("resumed run at wave {}", saved_run.wave_number + 1);
+    commands.remove_resource::<PendingRestore>();
+    SavedRun::remove();
+}
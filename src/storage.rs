@@ -0,0 +1,66 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Directory holding settings, high scores and saved runs.
+/// Can be overridden with the `LEGEND_OF_MIERDA_DATA` environment variable.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("LEGEND_OF_MIERDA_DATA") {
+        return dir.into();
+    }
+
+    let base = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(PathBuf::from)
+    } else {
+        std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| {
+                std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+            })
+    };
+
+    base.unwrap_or_else(|| PathBuf::from("."))
+        .join("legend_of_mierda")
+}
+
+/// Reads `file_name` from the data directory, `None` if it is missing or malformed.
+pub fn load<T: DeserializeOwned>(file_name: &str) -> Option<T> {
+    let path = data_dir().join(file_name);
+    let contents = std::fs::read_to_string(&path).ok()?;
+
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            warn!("ignoring malformed {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+pub fn save<T: Serialize>(file_name: &str, value: &T) {
+    let dir = data_dir();
+    let path = dir.join(file_name);
+
+    let result = std::fs::create_dir_all(&dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+                .map_err(|e| e.to_string())
+        })
+        .and_then(|contents| std::fs::write(&path, contents).map_err(|e| e.to_string()));
+
+    if let Err(e) = result {
+        error!("could not save {}: {}", path.display(), e);
+    }
+}
+
+pub fn remove(file_name: &str) {
+    let path = data_dir().join(file_name);
+
+    if let Err(e) = std::fs::remove_file(&path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            error!("could not remove {}: {}", path.display(), e);
+        }
+    }
+}
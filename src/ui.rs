@@ -3,6 +3,12 @@ use bevy::prelude::*;
 #[derive(Component)]
 pub struct UIPlayerHealth;
 
+#[derive(Component)]
+pub struct UIPlayerExperience;
+
+#[derive(Component)]
+pub struct UIPlayerLevel;
+
 #[derive(Component)]
 pub struct UIGameOver;
 
@@ -12,6 +18,9 @@ pub struct UIGameplayWave;
 #[derive(Component)]
 pub struct UIWeaponName;
 
+#[derive(Component)]
+pub struct UIWeaponLoadout;
+
 #[derive(Component)]
 pub struct UIHighscore;
 
@@ -94,40 +103,36 @@ pub(crate) fn draw_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
                 .insert(UIPlayerHealth);
         });
 
-    // Weapon - Gun
+    // experience bar
     commands
         .spawn((
             NodeBundle {
                 style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Px(10.0),
                     position_type: PositionType::Absolute,
                     justify_content: JustifyContent::FlexStart,
-                    bottom: Val::Px(25.0),
-                    right: Val::Px(5.0),
-                    padding: UiRect {
-                        right: Val::Px(15.0),
-                        ..default()
-                    },
-                    align_items: AlignItems::FlexStart,
+                    top: Val::Px(0.0),
                     ..default()
                 },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.5).into(),
                 ..default()
             },
             UIGamePlay,
-            Name::new("Weapon gun image"),
+            Name::new("ui experience bar"),
         ))
         .with_children(|parent| {
             parent.spawn((
                 NodeBundle {
                     style: Style {
-                        width: Val::Px(160.0),
-                        height: Val::Px(22.5),
-                        // margin: UiRect::top(Val::VMin(5.)),
+                        width: Val::Percent(0.0),
+                        height: Val::Percent(100.0),
                         ..default()
                     },
-                    background_color: Color::WHITE.into(),
+                    background_color: Color::CYAN.into(),
                     ..default()
                 },
-                UiImage::new(asset_server.load("sprites/speargun.png")),
+                UIPlayerExperience,
             ));
         });
 
@@ -135,105 +140,54 @@ pub(crate) fn draw_ui(mut commands: Commands, asset_server: Res<AssetServer>) {
         .spawn((
             NodeBundle {
                 style: Style {
+                    width: Val::Percent(100.0),
                     position_type: PositionType::Absolute,
-                    justify_content: JustifyContent::FlexStart,
-                    bottom: Val::Px(15.0),
-                    right: Val::Px(5.0),
-                    padding: UiRect {
-                        right: Val::Px(15.0),
-                        ..default()
-                    },
-                    align_items: AlignItems::FlexStart,
+                    justify_content: JustifyContent::Center,
+                    top: Val::Px(14.0),
                     ..default()
                 },
                 ..default()
             },
             UIGamePlay,
-            Name::new("Weapon name"),
+            Name::new("ui level text"),
         ))
         .with_children(|parent| {
             parent.spawn((
                 TextBundle::from_section(
-                    "SPEARGUN",
+                    "LV 1",
                     TextStyle {
                         font: asset_server.load("fonts/PixeloidMono-d94EV.ttf"),
                         font_size: 20.0,
-                        color: Color::WHITE,
+                        color: Color::CYAN,
                     },
                 ),
-                UIWeaponName,
+                UIPlayerLevel,
             ));
         });
 
-    // Weapon - Machete
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    position_type: PositionType::Absolute,
-                    justify_content: JustifyContent::FlexStart,
-                    bottom: Val::Px(100.0),
-                    right: Val::Px(5.0),
-                    padding: UiRect {
-                        right: Val::Px(15.0),
-                        ..default()
-                    },
-                    align_items: AlignItems::FlexStart,
+    // Weapons, filled in from the player's inventory
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::FlexEnd,
+                bottom: Val::Px(15.0),
+                right: Val::Px(5.0),
+                padding: UiRect {
+                    right: Val::Px(15.0),
                     ..default()
                 },
+                align_items: AlignItems::FlexStart,
+                row_gap: Val::Px(5.0),
                 ..default()
             },
-            UIGamePlay,
-            Name::new("Weapon machete  image"),
-        ))
-        .with_children(|parent| {
-            parent.spawn((
-                NodeBundle {
-                    style: Style {
-                        width: Val::Px(160.0),
-                        height: Val::Px(22.5),
-                        ..default()
-                    },
-                    background_color: Color::WHITE.into(),
-                    ..default()
-                },
-                UiImage::new(asset_server.load("sprites/machete.png")),
-            ));
-        });
-
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    position_type: PositionType::Absolute,
-                    justify_content: JustifyContent::FlexStart,
-                    bottom: Val::Px(85.0),
-                    right: Val::Px(5.0),
-                    padding: UiRect {
-                        right: Val::Px(15.0),
-                        ..default()
-                    },
-                    align_items: AlignItems::FlexStart,
-                    ..default()
-                },
-                ..default()
-            },
-            UIGamePlay,
-            Name::new("Weapon name"),
-        ))
-        .with_children(|parent| {
-            parent.spawn((
-                TextBundle::from_section(
-                    "MACHETE",
-                    TextStyle {
-                        font: asset_server.load("fonts/PixeloidMono-d94EV.ttf"),
-                        font_size: 20.0,
-                        color: Color::WHITE,
-                    },
-                ),
-                UIWeaponName,
-            ));
-        });
+            ..default()
+        },
+        UIGamePlay,
+        UIWeaponLoadout,
+        Name::new("Weapon loadout"),
+    ));
 
     // Wave
     commands